@@ -7,10 +7,13 @@ use std::{
 };
 
 use crate::{
-    cli::{Cli, Command},
-    command_handling,
+    cli::{Cli, Command, ConfigCommand, DownloadArgs},
+    command_handling, config,
     os_specifics::OS,
-    panic_handling, term_output, utils,
+    output_template::OutputTemplate,
+    panic_handling,
+    term_output::{self, ColorMode, OutputLevel},
+    utils,
 };
 
 pub const APP_NAME: &str = env!("CARGO_PKG_NAME");
@@ -40,19 +43,99 @@ impl std::fmt::Display for LogLevel {
 
 pub fn run(args: Cli, os: OS) -> Result<()> {
     initialize_logging(args.logging)?;
-    panic_handling::initialize_panic_hook(args.no_color)?;
+    let color = args.color_mode();
+    let output_level = args.output_level();
+    let quiet = output_level == OutputLevel::Quiet;
+    panic_handling::initialize_panic_hook(color)?;
     set_ctrl_c_handler()?;
+    let output_stream = if args.writes_data_to_stdout() {
+        term_output::get_stderr(color)
+    } else {
+        term_output::get_stdout(color)
+    };
     // execute the given command (download or local)
     let cmd_result = match args.command {
-        Command::Download(args) => command_handling::handle_download_cmd(args, os)?,
-        Command::Local(args) => command_handling::handle_local_cmd(args)?,
+        Command::Download(download_args) if download_args.from_file.is_some() => {
+            return run_batch_download(
+                download_args,
+                os,
+                color,
+                quiet,
+                args.format.as_ref(),
+                args.save,
+            );
+        }
+        Command::Download(args) => command_handling::handle_download_cmd(args, os, quiet)?,
+        Command::Local(args) => command_handling::handle_local_cmd(args, quiet)?,
+        Command::Config(args) => match args.command {
+            ConfigCommand::Show => {
+                let config = config::load()?;
+                let settings = config::settings(config.as_ref(), &config::env_var)?;
+                return term_output::print_settings(
+                    config.as_ref().map(|config| config.path.as_path()),
+                    &settings,
+                    output_stream,
+                );
+            }
+        },
     };
-    term_output::print_result(&cmd_result, args.no_color)?;
+    match &args.format {
+        Some(template) => term_output::print_formatted(&cmd_result, template, output_stream)?,
+        None => term_output::print_result(&cmd_result, output_level, output_stream)?,
+    }
     utils::save_hash_sum(&cmd_result, args.save)?;
 
+    // scripts rely on the exit status, in quiet mode it is the only result of the verification
+    if cmd_result.is_failure() {
+        log::info!("The verification failed - Exit with status 1");
+        std::process::exit(1);
+    }
+
     Ok(())
 }
 
+/// Downloads all files of a list file, prints the summary and fails if any download has failed
+fn run_batch_download(
+    args: DownloadArgs,
+    os: OS,
+    color: ColorMode,
+    quiet: bool,
+    format: Option<&OutputTemplate>,
+    save: bool,
+) -> Result<()> {
+    let results = command_handling::handle_batch_download_cmd(args, os, quiet)?;
+
+    match format {
+        // one line per downloaded file instead of the summary table
+        Some(template) => {
+            for cmd_result in results
+                .iter()
+                .filter_map(|entry_result| entry_result.result.as_ref().ok())
+            {
+                term_output::print_formatted(cmd_result, template, term_output::get_stdout(color))?;
+            }
+        }
+        None if quiet => {}
+        None => term_output::print_batch_summary(&results, color)?,
+    }
+
+    let cmd_results: Vec<_> = results
+        .iter()
+        .filter_map(|entry_result| entry_result.result.as_ref().ok())
+        .collect();
+    utils::save_hash_sums(&cmd_results, save)?;
+
+    let failed = results.iter().filter(|result| result.is_failure()).count();
+    if failed > 0 {
+        Err(anyhow::anyhow!(
+            "{failed} of {} download(s) failed",
+            results.len()
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 /// Initialize the application logging
 pub fn initialize_logging(log_level: Option<LogLevel>) -> Result<()> {
     create_data_dir()?;
@@ -219,6 +302,9 @@ pub fn version() -> String {
     let repo = env!("CARGO_PKG_REPOSITORY");
 
     let data_dir_path = utils::absolute_path_as_string(&data_dir());
+    let config_file_path = config::config_file()
+        .map(|config_file| utils::absolute_path_as_string(&config_file))
+        .unwrap_or_else(|| "none".to_string());
 
     format!(
         "\
@@ -228,6 +314,10 @@ pub fn version() -> String {
     Repository       : {repo}
 
     Data directory   : {data_dir_path}
+    Config file      : {config_file_path}
+
+    Options are taken from the command line, the environment (HASHGUARD_*) or the config file
+    in this order, 'hashguard config show' prints the source of each setting
     "
     )
 }
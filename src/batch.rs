@@ -0,0 +1,424 @@
+use std::{
+    collections::VecDeque,
+    path::Path,
+    sync::{Mutex, mpsc},
+    thread,
+};
+
+use anyhow::{Context, Result};
+use indicatif::MultiProgress;
+use serde::Deserialize;
+
+use crate::{
+    command_handling::CommandResult,
+    filename_handling,
+    hasher::{self, HashProperty},
+    http_client,
+    os_specifics::OS,
+    utils,
+};
+
+/// Default number of concurrent downloads
+pub const DEFAULT_JOBS: u8 = 4;
+
+/// A single download of a list file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub url: String,
+    pub hash_property: Option<HashProperty>,
+    pub file_name: Option<String>,
+}
+
+/// The outcome of a single download of a list file
+#[derive(Debug)]
+pub struct BatchEntryResult {
+    pub url: String,
+    pub result: Result<CommandResult>,
+}
+
+impl BatchEntryResult {
+    /// A download failed, if an error occurred, the hash sums do not match or
+    /// a digest advertised by the server does not match
+    pub fn is_failure(&self) -> bool {
+        match &self.result {
+            Ok(cmd_result) => cmd_result.is_failure(),
+            Err(_) => true,
+        }
+    }
+}
+
+/// Structure of a JSON or TOML manifest
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    downloads: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestEntry {
+    url: String,
+    hash: Option<String>,
+    name: Option<String>,
+}
+
+/// Reads the list of downloads from the given file.
+///
+/// Files with the extension `.json` or `.toml` are parsed as manifest, any other file
+/// is treated as a plain list with one `URL [HASH] [NAME]` entry per line.
+pub fn parse_list_file(path: &Path, os_type: &OS) -> Result<Vec<BatchEntry>> {
+    let content = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read list file: {}",
+            utils::absolute_path_as_string(path)
+        )
+    })?;
+
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+
+    let entries = match extension.as_deref() {
+        Some("json") => {
+            let manifest: Manifest =
+                serde_json::from_str(&content).with_context(|| "Failed to parse JSON manifest")?;
+            parse_manifest(manifest, os_type)?
+        }
+        Some("toml") => {
+            let manifest: Manifest =
+                toml::from_str(&content).with_context(|| "Failed to parse TOML manifest")?;
+            parse_manifest(manifest, os_type)?
+        }
+        _ => parse_plain_list(&content, os_type)?,
+    };
+
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!(
+            "The list file '{}' does not contain any download",
+            utils::absolute_path_as_string(path)
+        ));
+    }
+
+    Ok(entries)
+}
+
+/// Parses a list with one `URL [HASH] [NAME]` entry per line. Empty lines and lines starting with `#`
+/// are ignored. A `-` can be used as placeholder, if a name but no hash should be specified.
+/// It may be omitted, if the name contains a dot (e.g. a file extension).
+fn parse_plain_list(content: &str, os_type: &OS) -> Result<Vec<BatchEntry>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            let mut tokens = line.split_whitespace();
+            // the line is not empty, so there is at least one token
+            let url = tokens.next().unwrap_or_default();
+
+            let (hash, name) = match tokens.next() {
+                Some("-") => (None, tokens.next()),
+                // a hash never contains a dot, such a token is the start of the file name,
+                // any other token must be a valid hash, so that a mistyped hash is not used as name
+                Some(token) if token.contains('.') => (None, Some(token)),
+                token => (token, tokens.next()),
+            };
+
+            // file names may contain whitespaces
+            let name = name.map(|name| {
+                std::iter::once(name)
+                    .chain(tokens)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            });
+
+            build_entry(url, hash, name.as_deref(), os_type)
+                .with_context(|| format!("Invalid entry in line {line_number}"))
+        })
+        .collect()
+}
+
+fn parse_manifest(manifest: Manifest, os_type: &OS) -> Result<Vec<BatchEntry>> {
+    manifest
+        .downloads
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            build_entry(
+                &entry.url,
+                entry.hash.as_deref(),
+                entry.name.as_deref(),
+                os_type,
+            )
+            .with_context(|| format!("Invalid download entry number {}", idx + 1))
+        })
+        .collect()
+}
+
+/// Validates the parts of a download entry
+fn build_entry(
+    url: &str,
+    hash: Option<&str>,
+    name: Option<&str>,
+    os_type: &OS,
+) -> Result<BatchEntry> {
+    if !utils::is_valid_download_url(url) {
+        return Err(anyhow::anyhow!(
+            "Invalid URL: '{}'",
+            http_client::redact_url(url)
+        ));
+    }
+
+    let hash_property = match hash {
+        Some(hash) => Some(hasher::parse_hash(hash)?),
+        None => None,
+    };
+
+    if let Some(name) = name {
+        filename_handling::validate_filename(os_type, name)?;
+    }
+
+    Ok(BatchEntry {
+        url: url.to_string(),
+        hash_property,
+        file_name: name.map(str::to_string),
+    })
+}
+
+/// Processes all entries with the given number of worker threads.
+///
+/// The progress of all running downloads is displayed via a shared [`MultiProgress`].
+/// The results are returned in the same order as the given entries.
+pub fn execute_batch<F>(entries: Vec<BatchEntry>, jobs: u8, handler: F) -> Vec<BatchEntryResult>
+where
+    F: Fn(BatchEntry, &MultiProgress) -> Result<CommandResult> + Sync,
+{
+    let total = entries.len();
+    let workers = usize::from(jobs).clamp(1, total.max(1));
+
+    log::info!("Start batch download of {total} file(s) with {workers} concurrent download(s)");
+
+    let queue = Mutex::new(entries.into_iter().enumerate().collect::<VecDeque<_>>());
+    let multi_progress = MultiProgress::new();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for worker in 0..workers {
+            let sender = sender.clone();
+            let (queue, handler, multi_progress) = (&queue, &handler, &multi_progress);
+
+            let spawn_result = thread::Builder::new()
+                .name(format!("Download-Worker-Thread-{worker}"))
+                .spawn_scoped(scope, move || {
+                    loop {
+                        let next = match queue.lock() {
+                            Ok(mut queue) => queue.pop_front(),
+                            Err(_) => None,
+                        };
+                        let Some((idx, entry)) = next else {
+                            break;
+                        };
+
+                        let url = entry.url.clone();
+                        let result = handler(entry, multi_progress);
+                        if let Err(err) = &result {
+                            log::error!(
+                                "Download of '{}' failed - {err}",
+                                http_client::redact_url(&url)
+                            );
+                        }
+
+                        if sender
+                            .send((idx, BatchEntryResult { url, result }))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+
+            if let Err(e) = spawn_result {
+                log::error!("Failed to spawn Download-Worker-Thread - Details: {e:?}");
+            }
+        }
+    });
+
+    drop(sender);
+
+    let mut results: Vec<_> = receiver.into_iter().collect();
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HASH: &str = "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e";
+
+    #[test]
+    fn test_parse_plain_list() {
+        let content = format!(
+            "# comment\n\nhttps://example.com/a.zip\nhttps://example.com/b.zip {HASH}\n\
+             https://example.com/c.zip sha256:{HASH} c.zip\nhttps://example.com/d.zip - my file.zip\n\
+             https://example.com/e.zip e.zip\n"
+        );
+
+        let entries = parse_plain_list(&content, &OS::Linux).unwrap();
+        assert_eq!(entries.len(), 5);
+
+        assert_eq!(entries[0].url, "https://example.com/a.zip");
+        assert_eq!(entries[0].hash_property, None);
+        assert_eq!(entries[0].file_name, None);
+
+        assert_eq!(entries[1].hash_property.as_ref().unwrap().hash, HASH);
+        assert_eq!(entries[1].file_name, None);
+
+        assert_eq!(
+            entries[2].hash_property.as_ref().unwrap().algorithm,
+            Some(hasher::Algorithm::SHA2_256)
+        );
+        assert_eq!(entries[2].file_name.as_deref(), Some("c.zip"));
+
+        assert_eq!(entries[3].hash_property, None);
+        assert_eq!(entries[3].file_name.as_deref(), Some("my file.zip"));
+
+        assert_eq!(entries[4].hash_property, None);
+        assert_eq!(entries[4].file_name.as_deref(), Some("e.zip"));
+    }
+
+    #[test]
+    fn test_parse_plain_list_invalid_url() {
+        let content = "https://example.com/a.zip\nexample.com/b.zip\n";
+        let err = parse_plain_list(content, &OS::Linux).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid entry in line 2");
+    }
+
+    #[test]
+    fn test_parse_plain_list_invalid_hash() {
+        // a hash which is one character too short is not used as file name
+        let content = format!("https://example.com/a.zip {}\n", &HASH[1..]);
+        let err = parse_plain_list(&content, &OS::Linux).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid entry in line 1");
+
+        let content = format!("https://example.com/a.zip {}g a.zip\n", &HASH[1..]);
+        assert!(parse_plain_list(&content, &OS::Linux).is_err());
+
+        // a name without a dot requires the placeholder
+        let content = "https://example.com/a.zip - README\n";
+        let entries = parse_plain_list(content, &OS::Linux).unwrap();
+        assert_eq!(entries[0].file_name.as_deref(), Some("README"));
+        assert!(parse_plain_list("https://example.com/a.zip README\n", &OS::Linux).is_err());
+    }
+
+    #[test]
+    fn test_parse_plain_list_invalid_name() {
+        let content = format!("https://example.com/a.zip {HASH} dir/a.zip\n");
+        assert!(parse_plain_list(&content, &OS::Linux).is_err());
+    }
+
+    #[test]
+    fn test_parse_json_manifest() {
+        let content = format!(
+            r#"{{"downloads": [{{"url": "https://example.com/a.zip", "hash": "{HASH}", "name": "a.zip"}}, {{"url": "https://example.com/b.zip"}}]}}"#
+        );
+        let manifest: Manifest = serde_json::from_str(&content).unwrap();
+        let entries = parse_manifest(manifest, &OS::Linux).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].file_name.as_deref(), Some("a.zip"));
+        assert_eq!(entries[1].hash_property, None);
+    }
+
+    #[test]
+    fn test_parse_toml_manifest() {
+        let content = format!(
+            "[[downloads]]\nurl = \"https://example.com/a.zip\"\nhash = \"sha256:{HASH}\"\n\n\
+             [[downloads]]\nurl = \"https://example.com/b.zip\"\nname = \"b.zip\"\n"
+        );
+        let manifest: Manifest = toml::from_str(&content).unwrap();
+        let entries = parse_manifest(manifest, &OS::Linux).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].hash_property.as_ref().unwrap().algorithm,
+            Some(hasher::Algorithm::SHA2_256)
+        );
+        assert_eq!(entries[1].file_name.as_deref(), Some("b.zip"));
+    }
+
+    #[test]
+    fn test_execute_batch() {
+        use crate::command_handling::ResultDetails;
+        use std::{collections::HashSet, time::Duration};
+
+        let entries = |count: usize| {
+            (0..count)
+                .map(|idx| BatchEntry {
+                    url: format!("https://example.com/{idx}.zip"),
+                    hash_property: None,
+                    file_name: None,
+                })
+                .collect::<Vec<_>>()
+        };
+        // the URL of the entry is returned as hash, every third download fails
+        let worker_names = Mutex::new(HashSet::new());
+        let handler = |entry: BatchEntry, _: &MultiProgress| {
+            worker_names
+                .lock()
+                .unwrap()
+                .insert(thread::current().name().unwrap_or_default().to_string());
+            let idx: usize = entry.url[20..].trim_end_matches(".zip").parse()?;
+            // later entries finish first
+            thread::sleep(Duration::from_millis(10 * (10 - idx as u64 % 10)));
+            if idx % 3 == 0 {
+                anyhow::bail!("download {idx} failed");
+            }
+            Ok(CommandResult {
+                file_location: None,
+                buffer: None,
+                used_algorithm: hasher::Algorithm::SHA2_256,
+                calculated_hash_sum: entry.url,
+                hash_compare_result: None,
+                signature_verification: None,
+                download_source: None,
+                piece_verification: None,
+                integrity_checks: vec![],
+                redirect_chain: vec![],
+                from_cache: false,
+                details: ResultDetails::default(),
+            })
+        };
+
+        let results = execute_batch(entries(10), 4, handler);
+        assert_eq!(results.len(), 10);
+        for (idx, entry_result) in results.iter().enumerate() {
+            let url = format!("https://example.com/{idx}.zip");
+            assert_eq!(entry_result.url, url);
+            match &entry_result.result {
+                // a failed download does not affect the other entries
+                Err(err) => {
+                    assert_eq!(idx % 3, 0);
+                    assert_eq!(err.to_string(), format!("download {idx} failed"));
+                }
+                Ok(cmd_result) => {
+                    assert_ne!(idx % 3, 0);
+                    assert_eq!(cmd_result.calculated_hash_sum, url);
+                }
+            }
+        }
+        assert!(worker_names.lock().unwrap().len() <= 4);
+
+        // the number of workers is at least one and at most the number of entries
+        for (jobs, count, max_workers) in [(0, 3, 1), (16, 2, 2)] {
+            worker_names.lock().unwrap().clear();
+            let results = execute_batch(entries(count), jobs, handler);
+            assert_eq!(results.len(), count);
+            let names = worker_names.lock().unwrap();
+            assert!(!names.is_empty() && names.len() <= max_workers);
+            assert!(names.iter().all(|name| {
+                (0..max_workers).any(|worker| *name == format!("Download-Worker-Thread-{worker}"))
+            }));
+        }
+
+        assert!(execute_batch(vec![], 4, handler).is_empty());
+    }
+}
@@ -1,12 +1,23 @@
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
 
 use crate::{
-    cli::{DownloadArgs, LocalArgs},
-    download::{self, DownloadProperties},
-    hasher::{self, Algorithm},
-    local, os_specifics,
+    batch::{self, BatchEntryResult},
+    cli::{DownloadArgs, HttpArgs, LocalArgs},
+    download::{self, DownloadProperties, MirrorOrder, OutputMode},
+    filename_handling,
+    hasher::{self, Algorithm, HashProperty},
+    http_client::{self, HttpOptions},
+    integrity::IntegrityCheck,
+    local,
+    metalink::{self, PieceVerification},
+    os_specifics,
+    signature::{self, InvalidSignatureError, SignatureProperties, SignatureVerification},
+    utils,
 };
 
 #[derive(Debug)]
@@ -16,6 +27,50 @@ pub struct CommandResult {
     pub used_algorithm: Algorithm,
     pub calculated_hash_sum: String,
     pub hash_compare_result: Option<HashCompareResult>,
+    pub signature_verification: Option<SignatureVerification>,
+    /// The mirror from which the file was downloaded, if mirrors were given
+    pub download_source: Option<String>,
+    /// The result of the piece verification, if a Metalink file contains piece hashes
+    pub piece_verification: Option<PieceVerification>,
+    /// The results of the comparison with the digests advertised by the server
+    pub integrity_checks: Vec<IntegrityCheck>,
+    /// All URLs of the download request, if the server redirected the request
+    pub redirect_chain: Vec<String>,
+    /// `true` if the file of a previous download was reused, because the server reports that it is unchanged
+    pub from_cache: bool,
+    /// Details of the hash calculation, which are only printed in verbose mode
+    pub details: ResultDetails,
+}
+
+impl CommandResult {
+    /// The command failed, if the hash sums do not match, a piece hash or
+    /// a digest advertised by the server does not match
+    pub fn is_failure(&self) -> bool {
+        self.hash_compare_result
+            .as_ref()
+            .is_some_and(|compare_result| !compare_result.is_equal)
+            || self
+                .piece_verification
+                .as_ref()
+                .is_some_and(|verification| !verification.is_valid())
+            || self
+                .integrity_checks
+                .iter()
+                .any(|integrity_check| !integrity_check.is_valid)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ResultDetails {
+    /// Number of hashed bytes
+    pub size: Option<u64>,
+    /// Duration of the download or the hash calculation
+    pub elapsed: Option<Duration>,
+    /// The URL of the response after all redirects
+    pub final_url: Option<String>,
+    pub response_headers: Vec<(String, String)>,
+    /// Number of files in a hashed directory
+    pub walked_files: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -25,7 +80,15 @@ pub struct HashCompareResult {
 }
 
 // Handle the CLI subcommand 'download'
-pub fn handle_download_cmd(args: DownloadArgs, os_type: os_specifics::OS) -> Result<CommandResult> {
+pub fn handle_download_cmd(
+    args: DownloadArgs,
+    os_type: os_specifics::OS,
+    quiet: bool,
+) -> Result<CommandResult> {
+    let output_mode = args.output_mode();
+    // in quiet mode the user is never prompted, a missing file name is derived from the URL
+    let interactive = args.is_interactive() && !quiet;
+
     // fetch the output target
     let output_target = args.output;
 
@@ -38,11 +101,38 @@ pub fn handle_download_cmd(args: DownloadArgs, os_type: os_specifics::OS) -> Res
         }
     };
 
-    // get the download URL
-    let download_url = &args.url;
+    let http_options = http_options(args.http);
+
+    // a Metalink file provides the URLs and the hash of the file
+    let metalink_file = match args.metalink {
+        Some(ref source) => Some(metalink::load(source, &http_options)?),
+        None => None,
+    };
+
+    // get the download URL, its mirrors and the given hash
+    let (download_url, mirrors, hash_property) = match &metalink_file {
+        Some(metalink_file) => {
+            let mut urls = metalink_file.urls.clone();
+            let download_url = urls.remove(0);
+            (download_url, urls, metalink_file.hash.clone())
+        }
+        None => (
+            args.url
+                .ok_or_else(|| anyhow::anyhow!("A URL must be provided."))?,
+            args.mirrors,
+            args.hash_property,
+        ),
+    };
+
+    // if the user has not specified a filename, the name of the Metalink file is used
+    let default_file_name = args.rename.or_else(|| {
+        metalink_file
+            .as_ref()
+            .and_then(|metalink_file| metalink_file_name(&metalink_file.name, &os_type))
+    });
 
     // check if the given hash sum was prefixed by a hash algorithm, if so ignore the option [-a, --algorithm]
-    let algorithm = if let Some(ref hash_property) = args.hash_property {
+    let algorithm = if let Some(ref hash_property) = hash_property {
         match hash_property.algorithm {
             Some(algorithm) => algorithm,
             None => args.algorithm,
@@ -51,48 +141,287 @@ pub fn handle_download_cmd(args: DownloadArgs, os_type: os_specifics::OS) -> Res
         args.algorithm
     };
 
+    let signature_properties = args.signature.properties();
+
+    // a signature of the file itself can only be verified against a saved file
+    if output_mode != OutputMode::File
+        && args.checksum_file.is_none()
+        && signature_properties.is_some()
+    {
+        let msg = "A signature of the downloaded file can only be verified if the file is saved";
+        log::error!("{msg}");
+        return Err(anyhow::anyhow!(msg));
+    }
+
+    // verify the checksum file before the download starts, so that an invalid signature cancels the command early
+    let (checksum_file, mut signature_verification) = match args.checksum_file {
+        Some(ref source) => {
+            let (content, verification) =
+                load_checksum_file(source, signature_properties.as_ref(), &http_options)?;
+            (Some(content), verification)
+        }
+        None => (None, None),
+    };
+
+    // the entry is looked up by the name of the URL before the download, so that the next mirror
+    // is tried if the file does not match, the saved file may be renamed
+    let checksum_entry = match (
+        &checksum_file,
+        utils::extract_file_name_from_url(&download_url),
+    ) {
+        (Some(content), Some(file_name)) => Some(lookup_checksum(content, &file_name)?),
+        _ => None,
+    };
+    // the algorithm of the checksum file takes precedence over the option [-a, --algorithm]
+    let algorithm = checksum_entry
+        .as_ref()
+        .and_then(|checksum_entry| checksum_entry.algorithm)
+        .unwrap_or(algorithm);
+    let hash_property = checksum_entry.or(hash_property);
+
+    let has_mirrors = !mirrors.is_empty();
+
     // build the required DownloadProperties
     let download_properties = DownloadProperties {
         algorithm,
-        url: download_url.to_string(),
+        url: download_url,
+        mirrors,
+        mirror_order: args.mirror_order,
+        expected_hash: hash_property
+            .as_ref()
+            .map(|hash_property| hash_property.hash.clone()),
+        // the size given by the user takes precedence over the size of the Metalink file
+        expected_size: args.expected_size.or_else(|| {
+            metalink_file
+                .as_ref()
+                .and_then(|metalink_file| metalink_file.size)
+        }),
         output_target,
-        default_file_name: args.rename,
+        output_mode,
+        default_file_name,
+        interactive,
         os_type,
+        connections: args.connections,
+        limit_rate: args.limit_rate,
+        use_cache: !args.no_cache,
+        hash_raw: args.hash_raw,
+        decompress: args.decompress,
+        infer_extension: !args.no_infer_extension,
+        strict: args.strict,
+        quiet,
+        http_options: http_options.clone(),
+        multi_progress: None,
     };
 
     // start the download
     let download_result = download::execute_download(download_properties)?;
 
-    let cmd_result = if let Some(ref hash_property) = args.hash_property {
-        let given_hash = hash_property.hash.clone();
-        let is_hash_equal = hasher::is_hash_equal(&given_hash, &download_result.hash_sum);
+    // without a checksum file the signature belongs to the downloaded file
+    if let (None, Some(signature_properties), Some(file_location)) = (
+        &checksum_file,
+        &signature_properties,
+        &download_result.file_location,
+    ) {
+        let verification =
+            signature::verify_file(signature_properties, file_location, &http_options).map_err(
+                |verify_err| {
+                    if !verify_err.is::<InvalidSignatureError>() {
+                        return verify_err;
+                    }
+                    // a file with an invalid signature must not be mistaken for a verified download
+                    download::remove_invalid_file(file_location);
+                    anyhow::anyhow!(
+                        "{verify_err}\nThe downloaded file was removed: {}",
+                        utils::absolute_path_as_string(file_location)
+                    )
+                },
+            )?;
+        signature_verification = Some(verification);
+    }
 
-        CommandResult {
-            file_location: Some(download_result.file_location),
-            buffer: None,
-            used_algorithm: algorithm,
-            calculated_hash_sum: download_result.hash_sum,
-            hash_compare_result: Some(HashCompareResult {
-                is_equal: is_hash_equal,
-                given_hash,
-            }),
+    let piece_verification = match (
+        metalink_file
+            .as_ref()
+            .and_then(|metalink_file| metalink_file.pieces.as_ref()),
+        &download_result.file_location,
+    ) {
+        (Some(pieces), Some(file_location)) => {
+            Some(metalink::verify_pieces(file_location, pieces)?)
         }
-    } else {
-        CommandResult {
-            file_location: Some(download_result.file_location),
+        _ => None,
+    };
+
+    let (hash_property, used_algorithm, calculated_hash_sum) = match (&checksum_file, hash_property)
+    {
+        // without a name of the URL the entry is looked up by the name of the saved file
+        (Some(content), None) => {
+            let file_name = download_result
+                .file_location
+                .as_deref()
+                .map(file_name_of)
+                .unwrap_or_default();
+            let hash_property = lookup_checksum(content, &file_name)?;
+            match (hash_property.algorithm, &download_result.file_location) {
+                // the checksum file uses another algorithm -> hash the downloaded file again
+                (Some(checksum_algorithm), Some(file_location))
+                    if checksum_algorithm != algorithm =>
+                {
+                    let calculated_hash_sum = local::get_hash_for_object(
+                        file_location.clone(),
+                        checksum_algorithm,
+                        false,
+                        quiet,
+                    )?
+                    .hash_sum;
+                    (Some(hash_property), checksum_algorithm, calculated_hash_sum)
+                }
+                (Some(checksum_algorithm), None) if checksum_algorithm != algorithm => {
+                    let msg = format!(
+                        "The checksum file uses the algorithm {checksum_algorithm}, the data that was not saved was hashed with {algorithm}. Use the option [-a, --algorithm] to select {checksum_algorithm}"
+                    );
+                    log::error!("{msg}");
+                    return Err(anyhow::anyhow!(msg));
+                }
+                _ => (Some(hash_property), algorithm, download_result.hash_sum),
+            }
+        }
+        (_, hash_property) => (hash_property, algorithm, download_result.hash_sum),
+    };
+
+    // without a saved file the URL is the input source
+    let show_download_source = has_mirrors || download_result.file_location.is_none();
+
+    Ok(CommandResult {
+        file_location: download_result.file_location,
+        buffer: None,
+        used_algorithm,
+        hash_compare_result: compare_hash(hash_property.as_ref(), &calculated_hash_sum),
+        calculated_hash_sum,
+        signature_verification,
+        download_source: show_download_source
+            .then(|| http_client::redact_url(&download_result.source_url)),
+        piece_verification,
+        integrity_checks: download_result.integrity_checks,
+        redirect_chain: download_result.redirect_chain,
+        from_cache: download_result.from_cache,
+        details: ResultDetails {
+            size: Some(download_result.size),
+            elapsed: Some(download_result.elapsed),
+            final_url: download_result.final_url,
+            response_headers: download_result.response_headers,
+            walked_files: None,
+        },
+    })
+}
+
+// Handle the CLI subcommand 'download' with the option [-f, --from-file]
+pub fn handle_batch_download_cmd(
+    args: DownloadArgs,
+    os_type: os_specifics::OS,
+    quiet: bool,
+) -> Result<Vec<BatchEntryResult>> {
+    let list_file = args
+        .from_file
+        .ok_or_else(|| anyhow::anyhow!("A list file must be provided."))?;
+
+    let entries = batch::parse_list_file(&list_file, &os_type)?;
+
+    let output_target = args.output.unwrap_or_else(os_specifics::download_directory);
+
+    let http_options = http_options(args.http);
+
+    let results = batch::execute_batch(entries, args.jobs, |entry, multi_progress| {
+        // a prefixed hash overrides the option [-a, --algorithm]
+        let algorithm = entry
+            .hash_property
+            .as_ref()
+            .and_then(|hash_property| hash_property.algorithm)
+            .unwrap_or(args.algorithm);
+
+        let download_properties = DownloadProperties {
+            algorithm,
+            url: entry.url,
+            mirrors: vec![],
+            mirror_order: MirrorOrder::default(),
+            expected_hash: entry
+                .hash_property
+                .as_ref()
+                .map(|hash_property| hash_property.hash.clone()),
+            expected_size: None,
+            output_target: output_target.clone(),
+            output_mode: OutputMode::File,
+            default_file_name: entry.file_name,
+            // the downloads run concurrently, so the user can not be asked
+            interactive: false,
+            os_type,
+            connections: args.connections,
+            limit_rate: args.limit_rate,
+            use_cache: !args.no_cache,
+            hash_raw: args.hash_raw,
+            decompress: args.decompress,
+            infer_extension: !args.no_infer_extension,
+            strict: args.strict,
+            quiet,
+            http_options: http_options.clone(),
+            multi_progress: Some(multi_progress.clone()),
+        };
+
+        let download_result = download::execute_download(download_properties)?;
+
+        Ok(CommandResult {
+            file_location: download_result.file_location,
             buffer: None,
             used_algorithm: algorithm,
+            hash_compare_result: compare_hash(
+                entry.hash_property.as_ref(),
+                &download_result.hash_sum,
+            ),
             calculated_hash_sum: download_result.hash_sum,
-            hash_compare_result: None,
-        }
-    };
+            signature_verification: None,
+            download_source: None,
+            piece_verification: None,
+            integrity_checks: download_result.integrity_checks,
+            redirect_chain: download_result.redirect_chain,
+            from_cache: download_result.from_cache,
+            details: ResultDetails {
+                size: Some(download_result.size),
+                elapsed: Some(download_result.elapsed),
+                final_url: download_result.final_url,
+                response_headers: download_result.response_headers,
+                walked_files: None,
+            },
+        })
+    });
 
-    Ok(cmd_result)
+    Ok(results)
 }
 
 // Handle the CLI subcommand 'local'
-pub fn handle_local_cmd(args: LocalArgs) -> Result<CommandResult> {
-    let algorithm = if let Some(ref hash_property) = args.hash_sum {
+pub fn handle_local_cmd(args: LocalArgs, quiet: bool) -> Result<CommandResult> {
+    let signature_properties = args.signature.properties();
+    let http_options = http_options(args.http);
+
+    let (checksum_file, mut signature_verification) = match args.checksum_file {
+        Some(ref source) => {
+            let (content, verification) =
+                load_checksum_file(source, signature_properties.as_ref(), &http_options)?;
+            (Some(content), verification)
+        }
+        None => (None, None),
+    };
+
+    // the hash is either taken from the checksum file or given by the user
+    let hash_property = match (&checksum_file, &args.path) {
+        (Some(content), Some(path)) => Some(lookup_checksum(content, &file_name_of(path))?),
+        (Some(_), None) => {
+            return Err(anyhow::anyhow!(
+                "A checksum file can only be used in conjunction with a path."
+            ));
+        }
+        (None, _) => args.hash_sum,
+    };
+
+    let algorithm = if let Some(ref hash_property) = hash_property {
         match hash_property.algorithm {
             Some(algorithm) => algorithm,
             None => args.algorithm,
@@ -101,44 +430,195 @@ pub fn handle_local_cmd(args: LocalArgs) -> Result<CommandResult> {
         args.algorithm
     };
 
-    let (calculated_hash_sum, file_location, buffer) = if let Some(path) = args.path {
+    // without a checksum file the signature belongs to the file or buffer
+    if let (None, Some(signature_properties)) = (&checksum_file, &signature_properties) {
+        signature_verification = Some(match (&args.path, &args.buffer) {
+            (Some(path), _) if path.is_file() => {
+                signature::verify_file(signature_properties, path, &http_options)?
+            }
+            (None, Some(buffer)) => signature::verify_buffer(
+                signature_properties,
+                buffer.as_bytes(),
+                format!("Buffer of size {} byte(s)", buffer.len()),
+                &http_options,
+            )?,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "A signature can only be verified for a file or a buffer."
+                ));
+            }
+        });
+    }
+
+    let start = Instant::now();
+    let (calculated_hash_sum, file_location, buffer, details) = if let Some(path) = args.path {
         // calculate the file hash
-        let calculated_hash_sum =
-            local::get_hash_for_object(path.clone(), algorithm, args.include_names)?;
-        (calculated_hash_sum, Some(path), None)
+        let object_hash =
+            local::get_hash_for_object(path.clone(), algorithm, args.include_names, quiet)?;
+        let details = ResultDetails {
+            size: Some(object_hash.size),
+            walked_files: object_hash.walked_files,
+            ..Default::default()
+        };
+        (object_hash.hash_sum, Some(path), None, details)
     } else if let Some(some_text) = args.buffer {
         let buffer = some_text.as_bytes().to_vec();
         let calculated_hash_sum = local::get_buffer_hash(&buffer, algorithm);
-        (calculated_hash_sum, None, Some(some_text))
+        let details = ResultDetails {
+            size: Some(buffer.len() as u64),
+            ..Default::default()
+        };
+        (calculated_hash_sum, None, Some(some_text), details)
     } else {
         return Err(anyhow::anyhow!(
             "Either a path or a buffer must be provided."
         ));
     };
 
-    let cmd_result = if let Some(ref hash_property) = args.hash_sum {
-        let given_hash = hash_property.hash.clone();
-        let is_hash_equal = hasher::is_hash_equal(&given_hash, &calculated_hash_sum);
+    Ok(CommandResult {
+        file_location,
+        buffer,
+        used_algorithm: algorithm,
+        hash_compare_result: compare_hash(hash_property.as_ref(), &calculated_hash_sum),
+        calculated_hash_sum,
+        signature_verification,
+        download_source: None,
+        piece_verification: None,
+        integrity_checks: vec![],
+        redirect_chain: vec![],
+        from_cache: false,
+        details: ResultDetails {
+            elapsed: Some(start.elapsed()),
+            ..details
+        },
+    })
+}
 
-        CommandResult {
-            file_location,
-            buffer,
-            used_algorithm: algorithm,
-            calculated_hash_sum: calculated_hash_sum.to_string(),
-            hash_compare_result: Some(HashCompareResult {
-                is_equal: is_hash_equal,
-                given_hash,
-            }),
-        }
-    } else {
-        CommandResult {
-            file_location,
-            buffer,
-            used_algorithm: algorithm,
-            calculated_hash_sum: calculated_hash_sum.to_string(),
-            hash_compare_result: None,
-        }
+/// Compares the given hash with the calculated hash sum
+fn compare_hash(
+    hash_property: Option<&HashProperty>,
+    calculated_hash_sum: &str,
+) -> Option<HashCompareResult> {
+    hash_property.map(|hash_property| HashCompareResult {
+        is_equal: hasher::is_hash_equal(&hash_property.hash, calculated_hash_sum),
+        given_hash: hash_property.hash.clone(),
+    })
+}
+
+/// Returns the settings of the HTTP client and warns the user, if the certificate verification is disabled
+fn http_options(args: HttpArgs) -> HttpOptions {
+    let http_options = args.options();
+
+    if http_options.insecure {
+        eprintln!(
+            "WARNING: The verification of TLS certificates is disabled [--insecure] - The identity of the server is NOT verified!"
+        );
+    }
+
+    http_options
+}
+
+/// Returns the name of a file described by a Metalink file, if it is a valid filename.
+/// The name may contain a directory path, which is ignored.
+fn metalink_file_name(name: &str, os_type: &os_specifics::OS) -> Option<String> {
+    let file_name = name.rsplit(['/', '\\']).next()?;
+
+    let sanitized_name = filename_handling::sanitize_filename(os_type, file_name);
+    if sanitized_name.is_none() {
+        log::warn!("Invalid filename '{name}' in Metalink file");
+    }
+    sanitized_name
+}
+
+/// Loads the checksum file and verifies the signature against it, if a signature was given
+fn load_checksum_file(
+    source: &str,
+    signature_properties: Option<&SignatureProperties>,
+    http_options: &HttpOptions,
+) -> Result<(String, Option<SignatureVerification>)> {
+    let content = signature::load_source(source, http_options)?;
+
+    let signature_verification = match signature_properties {
+        Some(signature_properties) => Some(signature::verify_buffer(
+            signature_properties,
+            &content,
+            source.to_string(),
+            http_options,
+        )?),
+        None => None,
     };
 
-    Ok(cmd_result)
+    Ok((
+        String::from_utf8_lossy(&content).to_string(),
+        signature_verification,
+    ))
+}
+
+/// Returns the name of the given file
+fn file_name_of(file: &Path) -> String {
+    file.file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Looks up the hash of the given file name in the content of a checksum file
+fn lookup_checksum(checksum_file: &str, file_name: &str) -> Result<HashProperty> {
+    hasher::find_hash_in_checksum_file(checksum_file, file_name).ok_or_else(|| {
+        let msg = format!("The checksum file contains no hash for '{file_name}'");
+        log::error!("{msg}");
+        anyhow::anyhow!(msg)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli::{Cli, Command};
+    use clap::Parser;
+
+    const HASH: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn test_checksum_file_mirror_fallback() {
+        let test_dir =
+            std::env::temp_dir().join(format!("hashguard_{}_checksum", std::process::id()));
+        for (mirror, content) in [("first", "world"), ("second", "hello")] {
+            std::fs::create_dir_all(test_dir.join(mirror)).unwrap();
+            std::fs::write(test_dir.join(mirror).join("file.txt"), content).unwrap();
+        }
+        let checksum_file = test_dir.join("SHA256SUMS");
+        std::fs::write(&checksum_file, format!("{HASH}  file.txt\n")).unwrap();
+        let output = test_dir.join("output");
+        std::fs::create_dir_all(&output).unwrap();
+
+        let file_url =
+            |mirror: &str| utils::file_url(&test_dir.join(mirror).join("file.txt")).unwrap();
+        let cli = Cli::try_parse_from([
+            "hashguard",
+            "download",
+            &file_url("first"),
+            "--mirror",
+            &file_url("second"),
+            "--checksum-file",
+            &checksum_file.to_string_lossy(),
+            "--output",
+            &output.to_string_lossy(),
+            "--rename",
+            "renamed.txt",
+            "--no-cache",
+        ])
+        .unwrap();
+        let Command::Download(args) = cli.command else {
+            panic!("download command expected");
+        };
+
+        // the file of the first URL does not match the checksum entry -> the mirror is used
+        let cmd_result = handle_download_cmd(args, os_specifics::OS::Linux, true).unwrap();
+        assert_eq!(cmd_result.download_source, Some(file_url("second")));
+        assert_eq!(cmd_result.calculated_hash_sum, HASH);
+        assert!(cmd_result.hash_compare_result.unwrap().is_equal);
+        assert_eq!(std::fs::read(output.join("renamed.txt")).unwrap(), b"hello");
+
+        std::fs::remove_dir_all(test_dir).unwrap();
+    }
 }
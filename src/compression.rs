@@ -0,0 +1,341 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use ruzstd::decoding::{
+    StreamingDecoder,
+    errors::{FrameDecoderError, ReadFrameHeaderError},
+};
+use ureq::http::{HeaderMap, header::CONTENT_ENCODING};
+
+use crate::{
+    hasher::{Algorithm, Hasher},
+    utils,
+};
+
+/// Content codings, which can be requested from the server and decoded
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ContentCoding {
+    /// no encoding
+    Identity,
+    Gzip,
+    /// zlib compressed data
+    Deflate,
+}
+
+impl ContentCoding {
+    fn name(&self) -> &'static str {
+        match self {
+            ContentCoding::Identity => "identity",
+            ContentCoding::Gzip => "gzip",
+            ContentCoding::Deflate => "deflate",
+        }
+    }
+}
+
+/// Returns the value of the `Accept-Encoding` header for the given content codings
+pub fn accept_encoding(codings: &[ContentCoding]) -> String {
+    codings
+        .iter()
+        .map(ContentCoding::name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the content coding of the server response, `None` if the body is not encoded
+pub fn content_coding(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(CONTENT_ENCODING)
+        .and_then(|header_value| header_value.to_str().ok())
+        .map(|coding| coding.trim().to_lowercase())
+        .filter(|coding| !coding.is_empty() && coding != "identity")
+}
+
+/// Wraps the reader with a decoder for the content coding of the server response
+pub fn decode_content<'a>(reader: impl Read + 'a, coding: &str) -> Result<Box<dyn Read + 'a>> {
+    match coding {
+        "gzip" | "x-gzip" => Ok(Box::new(MultiGzDecoder::new(reader))),
+        "deflate" => Ok(Box::new(ZlibDecoder::new(reader))),
+        _ => {
+            let msg = format!(
+                "The server response is encoded with the unsupported content coding '{coding}'. Use the option [--hash-raw] to save the encoded data"
+            );
+            log::error!("{msg}");
+            Err(anyhow::anyhow!(msg))
+        }
+    }
+}
+
+/// Compression formats of downloaded artifacts, which can be decompressed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompressionFormat {
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+impl CompressionFormat {
+    /// Detects the compression format by the magic bytes of the file
+    pub fn detect(file: &Path) -> Result<Option<Self>> {
+        let mut magic_bytes = Vec::with_capacity(6);
+        File::open(file)
+            .and_then(|file| file.take(6).read_to_end(&mut magic_bytes))
+            .map_err(|io_err| {
+                let msg = format!(
+                    "Failed to read file: {}",
+                    utils::absolute_path_as_string(file)
+                );
+                log::error!("{msg} - Details: {io_err:?}");
+                anyhow::anyhow!(msg)
+            })?;
+
+        let format = match magic_bytes.as_slice() {
+            [0x1f, 0x8b, ..] => Some(CompressionFormat::Gzip),
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00] => Some(CompressionFormat::Xz),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(CompressionFormat::Zstd),
+            _ => None,
+        };
+
+        Ok(format)
+    }
+
+    // the extensions of a file in this format, a compressed tar archive is named e.g. '.tgz'
+    fn extensions(&self) -> (&'static str, &'static str) {
+        match self {
+            CompressionFormat::Gzip => (".gz", ".tgz"),
+            CompressionFormat::Xz => (".xz", ".txz"),
+            CompressionFormat::Zstd => (".zst", ".tzst"),
+        }
+    }
+}
+
+/// Returns the path of the decompressed file, the extension of the compression format is removed.
+/// If the file has no such extension, '.out' is appended to the name.
+pub fn decompressed_path(file: &Path, format: CompressionFormat) -> PathBuf {
+    let file_name = file
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lowercase_name = file_name.to_lowercase();
+    let (extension, tar_extension) = format.extensions();
+
+    let decompressed_name = if lowercase_name.ends_with(tar_extension) {
+        format!(
+            "{}.tar",
+            &file_name[..file_name.len() - tar_extension.len()]
+        )
+    } else if lowercase_name.ends_with(extension) && lowercase_name.len() > extension.len() {
+        file_name[..file_name.len() - extension.len()].to_string()
+    } else {
+        format!("{file_name}.out")
+    };
+
+    file.with_file_name(decompressed_name)
+}
+
+/// Decompresses the file into the target and returns the hash sum of the decompressed data
+pub fn decompress_file(
+    file: &Path,
+    target: &Path,
+    format: CompressionFormat,
+    algorithm: Algorithm,
+) -> Result<String> {
+    log::info!(
+        "Decompress {format:?} file: {} -> {}",
+        utils::absolute_path_as_string(file),
+        utils::absolute_path_as_string(target)
+    );
+
+    let read_err = |io_err: std::io::Error| {
+        let msg = format!(
+            "Failed to decompress file: {}",
+            utils::absolute_path_as_string(file)
+        );
+        log::error!("{msg} - Details: {io_err:?}");
+        anyhow::anyhow!(msg)
+    };
+
+    let compressed_file = File::open(file).map_err(read_err)?;
+    let mut reader = BufReader::with_capacity(utils::CAPACITY, compressed_file);
+
+    let target_file = File::create(target).map_err(|io_err| {
+        let msg = format!(
+            "Failed to create file: {}",
+            utils::absolute_path_as_string(target)
+        );
+        log::error!("{msg} - Details: {io_err:?}");
+        anyhow::anyhow!(msg)
+    })?;
+    let mut writer = HashWriter {
+        writer: BufWriter::with_capacity(utils::CAPACITY, target_file),
+        hasher: Hasher::new(algorithm),
+    };
+
+    match format {
+        CompressionFormat::Gzip => {
+            std::io::copy(&mut MultiGzDecoder::new(reader), &mut writer).map(drop)
+        }
+        CompressionFormat::Xz => lzma_rs::xz_decompress(&mut reader, &mut writer)
+            .map_err(|xz_err| std::io::Error::new(std::io::ErrorKind::InvalidData, xz_err)),
+        CompressionFormat::Zstd => decompress_zstd(&mut reader, &mut writer),
+    }
+    .and_then(|_| writer.writer.flush())
+    .map_err(read_err)?;
+
+    Ok(hex::encode(writer.hasher.finalize()))
+}
+
+/// Decompresses all frames of a zstd file, skippable frames (e.g. metadata) are ignored
+fn decompress_zstd(mut reader: impl BufRead, writer: &mut impl Write) -> std::io::Result<()> {
+    while !reader.fill_buf()?.is_empty() {
+        match StreamingDecoder::new(&mut reader) {
+            Ok(mut decoder) => {
+                std::io::copy(&mut decoder, writer)?;
+            }
+            Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame {
+                length,
+                ..
+            })) => {
+                std::io::copy(
+                    &mut reader.by_ref().take(length as u64),
+                    &mut std::io::sink(),
+                )?;
+            }
+            Err(zstd_err) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    zstd_err,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the data into the inner writer and hashes the written bytes
+struct HashWriter<W: Write> {
+    writer: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let bytes_written = self.writer.write(buf)?;
+        self.hasher.update(&buf[..bytes_written]);
+        Ok(bytes_written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use flate2::{Compression, write::GzEncoder};
+    use ruzstd::encoding::{CompressionLevel, compress_to_vec};
+
+    const DATA: &[u8] = b"hashguard - verify the integrity of your files";
+    const DATA_HASH: &str = "86e45f05594517fe05e40bff7cb62f43a3134763eb2613c2b32121fb323d3dad";
+
+    fn decompress_test_file(format: CompressionFormat, compressed: &[u8]) -> (Vec<u8>, String) {
+        let file = std::env::temp_dir().join(format!(
+            "hashguard_{}_decompress.{format:?}",
+            std::process::id()
+        ));
+        let target = decompressed_path(&file, format);
+        std::fs::write(&file, compressed).unwrap();
+
+        assert_eq!(CompressionFormat::detect(&file).unwrap(), Some(format));
+        let hash_sum = decompress_file(&file, &target, format, Algorithm::SHA2_256);
+        let decompressed = std::fs::read(&target).unwrap();
+
+        std::fs::remove_file(&file).unwrap();
+        std::fs::remove_file(&target).unwrap();
+        (decompressed, hash_sum.unwrap())
+    }
+
+    #[test]
+    fn test_decompressed_path() {
+        assert_eq!(
+            decompressed_path(Path::new("/tmp/release.tar.gz"), CompressionFormat::Gzip),
+            PathBuf::from("/tmp/release.tar")
+        );
+        assert_eq!(
+            decompressed_path(Path::new("/tmp/release.TXZ"), CompressionFormat::Xz),
+            PathBuf::from("/tmp/release.tar")
+        );
+        assert_eq!(
+            decompressed_path(Path::new("/tmp/release"), CompressionFormat::Zstd),
+            PathBuf::from("/tmp/release.out")
+        );
+    }
+
+    #[test]
+    fn test_decode_content() {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(b"hashguard").unwrap();
+        let encoded = encoder.finish().unwrap();
+
+        let mut decoded = String::new();
+        decode_content(encoded.as_slice(), "gzip")
+            .unwrap()
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "hashguard");
+
+        assert!(decode_content(encoded.as_slice(), "br").is_err());
+    }
+
+    #[test]
+    fn test_decompress_file() {
+        let mut gzip = GzEncoder::new(vec![], Compression::default());
+        gzip.write_all(DATA).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        let mut xz = vec![];
+        lzma_rs::xz_compress(&mut &DATA[..], &mut xz).unwrap();
+
+        // a file with several frames and a skippable frame, e.g. written by 'pzstd'
+        let (first, second) = DATA.split_at(10);
+        let mut zstd = compress_to_vec(first, CompressionLevel::Fastest);
+        zstd.extend_from_slice(&[0x50, 0x2a, 0x4d, 0x18, 3, 0, 0, 0, 1, 2, 3]);
+        zstd.extend(compress_to_vec(second, CompressionLevel::Fastest));
+
+        for (format, compressed) in [
+            (CompressionFormat::Gzip, gzip),
+            (CompressionFormat::Xz, xz),
+            (CompressionFormat::Zstd, zstd),
+        ] {
+            let (decompressed, hash_sum) = decompress_test_file(format, &compressed);
+            assert_eq!(decompressed, DATA, "{format:?}");
+            assert_eq!(hash_sum, DATA_HASH, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_decompress_corrupted_file() {
+        let mut xz = vec![];
+        lzma_rs::xz_compress(&mut &DATA[..], &mut xz).unwrap();
+        xz.truncate(xz.len() - 8);
+
+        let file =
+            std::env::temp_dir().join(format!("hashguard_{}_corrupted.xz", std::process::id()));
+        let target = decompressed_path(&file, CompressionFormat::Xz);
+        std::fs::write(&file, xz).unwrap();
+
+        assert!(
+            decompress_file(&file, &target, CompressionFormat::Xz, Algorithm::SHA2_256).is_err()
+        );
+
+        std::fs::remove_file(&file).unwrap();
+        let _ = std::fs::remove_file(&target);
+    }
+}
@@ -0,0 +1,577 @@
+use std::{ffi::OsString, fmt, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{
+    Arg, ArgAction, ArgMatches, Command, CommandFactory, builder::BoolishValueParser,
+    parser::ValueSource,
+};
+use toml::{Table, Value};
+use ureq::Proxy;
+
+use crate::{app, cli::Cli, utils};
+
+/// Environment variable with the path of the config file, replaces the default locations
+pub const CONFIG_ENV: &str = "HASHGUARD_CONFIG";
+
+// prefix of the environment variables of the options, e.g. 'HASHGUARD_ALGORITHM' for '--algorithm'
+const ENV_PREFIX: &str = "HASHGUARD_";
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+// the options of these commands are set in a table of the same name, e.g. '[download]'
+const COMMAND_TABLES: [&str; 2] = ["download", "local"];
+
+/// Origin of the value of a setting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingSource {
+    Default,
+    ConfigFile,
+    /// the name of the environment variable
+    Environment(String),
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingSource::Default => write!(f, "default"),
+            SettingSource::ConfigFile => write!(f, "config file"),
+            SettingSource::Environment(name) => write!(f, "environment {name}"),
+        }
+    }
+}
+
+/// The effective value of an option, which is used if the option is not given on the command line
+#[derive(Debug)]
+pub struct Setting {
+    /// `global` or the name of the command
+    pub scope: String,
+    pub name: String,
+    pub value: String,
+    pub source: SettingSource,
+}
+
+/// The content of the config file
+#[derive(Debug)]
+pub struct Config {
+    pub path: PathBuf,
+    table: Table,
+}
+
+/// Returns the path of the config file: the path of the environment variable `HASHGUARD_CONFIG`,
+/// otherwise the first existing file of the default locations
+pub fn config_file() -> Option<PathBuf> {
+    match std::env::var_os(CONFIG_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => default_locations().into_iter().find(|path| path.is_file()),
+    }
+}
+
+/// The default locations of the config file: the config directory of the OS (e.g. `~/.config/hashguard`)
+/// and the application data directory
+pub fn default_locations() -> Vec<PathBuf> {
+    dirs::config_dir()
+        .map(|config_dir| config_dir.join(app::APP_NAME).join(CONFIG_FILE_NAME))
+        .into_iter()
+        .chain([app::data_dir().join(CONFIG_FILE_NAME)])
+        .collect()
+}
+
+/// Loads the config file, returns `None` if no config file exists
+pub fn load() -> Result<Option<Config>> {
+    let Some(path) = config_file() else {
+        return Ok(None);
+    };
+
+    let content = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "Failed to read config file: {}",
+            utils::absolute_path_as_string(&path)
+        )
+    })?;
+
+    let table = content.parse::<Table>().with_context(|| {
+        format!(
+            "Failed to parse config file: {}",
+            utils::absolute_path_as_string(&path)
+        )
+    })?;
+
+    Ok(Some(Config { path, table }))
+}
+
+impl Config {
+    /// Sets the values of the config file as default values of the options,
+    /// so that options on the command line take precedence
+    pub fn apply(&self, mut command: Command) -> Result<Command> {
+        for (id, values) in self.defaults(&command, None)? {
+            command = command.mut_arg(id, |arg| arg.default_values(values));
+        }
+
+        for name in COMMAND_TABLES {
+            let Some(sub_command) = command.find_subcommand(name) else {
+                continue;
+            };
+
+            for (id, values) in self.defaults(sub_command, Some(name))? {
+                command = command.mut_subcommand(name, |sub_command| {
+                    sub_command.mut_arg(id, |arg| arg.default_values(values))
+                });
+            }
+        }
+
+        Ok(command)
+    }
+
+    /// Returns `true` if the config file contains a value for the option
+    fn contains(&self, command_name: Option<&str>, arg: &Arg) -> bool {
+        let table = match command_name {
+            Some(name) => self.table.get(name).and_then(Value::as_table),
+            None => Some(&self.table),
+        };
+
+        table.is_some_and(|table| {
+            table
+                .keys()
+                .any(|key| option_name(key) == arg.get_long().unwrap_or_default())
+        })
+    }
+
+    /// Returns the IDs of the options and their values from the table of the command
+    fn defaults(
+        &self,
+        command: &Command,
+        command_name: Option<&str>,
+    ) -> Result<Vec<(String, Vec<String>)>> {
+        let table = match command_name {
+            Some(name) => match self.table.get(name) {
+                Some(Value::Table(table)) => table,
+                Some(_) => {
+                    anyhow::bail!("Invalid config file: '{name}' must be a table, e.g. [{name}]")
+                }
+                None => return Ok(vec![]),
+            },
+            None => &self.table,
+        };
+
+        let scope = command_name.unwrap_or("global");
+        let mut defaults = vec![];
+
+        for (key, value) in table {
+            if command_name.is_none() && COMMAND_TABLES.contains(&key.as_str()) {
+                continue;
+            }
+
+            let name = option_name(key);
+            let arg = command
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(name.as_str()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Invalid config file: unknown option '{key}' in {scope} settings"
+                    )
+                })?;
+
+            // the proxy of the environment takes precedence over the config file
+            if name == "proxy" && Proxy::try_from_env().is_some() {
+                log::info!("Ignore proxy of the config file, a proxy is set in the environment");
+                continue;
+            }
+
+            let values = config_values(value).with_context(|| {
+                format!("Invalid config file: invalid value of '{key}' in {scope} settings")
+            })?;
+
+            defaults.push((arg.get_id().to_string(), values));
+        }
+
+        Ok(defaults)
+    }
+}
+
+/// Sets the values of the environment variables as default values of the options, e.g. the value of
+/// `HASHGUARD_CONNECT_TIMEOUT` for `--connect-timeout`. The value of a variable is returned by `env_var`.
+///
+/// Applied after the config file, a value of the environment takes precedence over the config file.
+/// As default value it is not treated as given on the command line, so that options on the
+/// command line take precedence and `requires` or `conflicts_with` of the option do not apply.
+pub fn apply_env(mut command: Command, env_var: &impl Fn(&str) -> Option<String>) -> Command {
+    command = command.mut_args(|arg| with_env_value(arg, env_var));
+
+    for name in COMMAND_TABLES {
+        command = command.mut_subcommand(name, |sub_command| {
+            sub_command.mut_args(|arg| with_env_value(arg, env_var))
+        });
+    }
+
+    command
+}
+
+fn with_env_value(arg: Arg, env_var: &impl Fn(&str) -> Option<String>) -> Arg {
+    let Some(value) = env_name(&arg).and_then(|env_name| env_var(&env_name)) else {
+        return arg;
+    };
+    let arg = arg.default_value(value);
+
+    // the value of a flag in the environment may also be e.g. '1' or 'yes'
+    match arg.get_action() {
+        ArgAction::SetTrue => arg.value_parser(BoolishValueParser::new()),
+        _ => arg,
+    }
+}
+
+/// Returns the name of the environment variable of the option, e.g. `HASHGUARD_CONNECT_TIMEOUT`.
+/// Positional arguments, e.g. the URL, help and version have no environment variable.
+fn env_name(arg: &Arg) -> Option<String> {
+    if matches!(arg.get_id().as_str(), "help" | "version") {
+        return None;
+    }
+
+    arg.get_long()
+        .map(|long| format!("{ENV_PREFIX}{}", long.to_uppercase().replace('-', "_")))
+}
+
+/// Returns the value of an environment variable, an empty value is treated as not set
+pub fn env_var(name: &str) -> Option<String> {
+    std::env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string_lossy().to_string())
+}
+
+/// Parses the arguments of the command line. A default value of the environment or of the config file,
+/// which conflicts with an option on the command line, is dropped, e.g. `HASHGUARD_VERBOSE=1` if `--quiet` is given.
+pub fn get_matches_from(command: Command, args: Vec<OsString>) -> Result<ArgMatches, clap::Error> {
+    // the conflicts are looked up in the built command, the changed command must be built again
+    let mut built_command = command.clone();
+    built_command.build();
+    let matches = built_command.clone().try_get_matches_from(&args)?;
+
+    let mut dropped: Vec<(Option<String>, String)> = conflicting_defaults(&built_command, &matches)
+        .into_iter()
+        .map(|id| (None, id))
+        .collect();
+    let sub_command = matches
+        .subcommand()
+        .and_then(|(name, sub_matches)| Some((built_command.find_subcommand(name)?, sub_matches)));
+    if let Some((sub_command, sub_matches)) = sub_command {
+        dropped.extend(
+            conflicting_defaults(sub_command, sub_matches)
+                .into_iter()
+                .map(|id| (Some(sub_command.get_name().to_string()), id)),
+        );
+    }
+
+    if dropped.is_empty() {
+        return Ok(matches);
+    }
+
+    // the dropped options get the default value of the options again
+    let mut original = Cli::command();
+    original.build();
+
+    let mut command = command;
+    for (command_name, id) in dropped {
+        let original_command = match &command_name {
+            Some(name) => original.find_subcommand(name).unwrap_or(&original),
+            None => &original,
+        };
+        let default_values: Vec<_> = original_command
+            .get_arguments()
+            .find(|arg| *arg.get_id() == id)
+            .map(|arg| arg.get_default_values().to_vec())
+            .unwrap_or_default();
+
+        log::info!(
+            "Ignore the default value of '{id}', a conflicting option is given on the command line"
+        );
+        command = match command_name {
+            Some(name) => command.mut_subcommand(name, |sub_command| {
+                sub_command.mut_arg(&id, |arg| arg.default_values(default_values))
+            }),
+            None => command.mut_arg(&id, |arg| arg.default_values(default_values)),
+        };
+    }
+
+    command.try_get_matches_from(args)
+}
+
+/// Returns the IDs of all options with a default value, which conflict with an option on the command line
+fn conflicting_defaults(command: &Command, matches: &ArgMatches) -> Vec<String> {
+    let source = |arg: &Arg| matches.value_source(arg.get_id().as_str());
+    let given: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| source(arg) == Some(ValueSource::CommandLine))
+        .collect();
+
+    // a conflict is declared by only one of both options
+    let conflicts = |a: &Arg, b: &Arg| {
+        command
+            .get_arg_conflicts_with(a)
+            .iter()
+            .any(|conflict| conflict.get_id() == b.get_id())
+    };
+
+    command
+        .get_arguments()
+        .filter(|arg| source(arg) == Some(ValueSource::DefaultValue))
+        .filter(|arg| {
+            given
+                .iter()
+                .any(|given| conflicts(arg, given) || conflicts(given, arg))
+        })
+        .map(|arg| arg.get_id().to_string())
+        .collect()
+}
+
+/// Returns the effective settings of all commands, which are used if an option is not given on the command line
+pub fn settings(
+    config: Option<&Config>,
+    env_var: &impl Fn(&str) -> Option<String>,
+) -> Result<Vec<Setting>> {
+    let command = match config {
+        Some(config) => config.apply(Cli::command())?,
+        None => Cli::command(),
+    };
+    let mut command = apply_env(command, env_var);
+    // the default values of the flags are only set, when the command is built
+    command.build();
+
+    let mut settings = collect_settings(&command, None, config, env_var);
+    for name in COMMAND_TABLES {
+        if let Some(sub_command) = command.find_subcommand(name) {
+            settings.extend(collect_settings(sub_command, Some(name), config, env_var));
+        }
+    }
+
+    Ok(settings)
+}
+
+fn collect_settings(
+    command: &Command,
+    command_name: Option<&str>,
+    config: Option<&Config>,
+    env_var: &impl Fn(&str) -> Option<String>,
+) -> Vec<Setting> {
+    let mut settings: Vec<_> = command
+        .get_arguments()
+        .filter(|arg| !matches!(arg.get_id().as_str(), "help" | "version"))
+        .filter(|arg| arg.get_long().is_some())
+        .filter_map(|arg| {
+            let env_value =
+                env_name(arg).and_then(|env_name| Some((env_var(&env_name)?, env_name)));
+
+            let (value, source) = match env_value {
+                Some((env_value, env_name)) => (env_value, SettingSource::Environment(env_name)),
+                None if arg.get_default_values().is_empty() => return None,
+                None => (
+                    arg.get_default_values()
+                        .iter()
+                        .map(|value| value.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    if config.is_some_and(|config| config.contains(command_name, arg)) {
+                        SettingSource::ConfigFile
+                    } else {
+                        SettingSource::Default
+                    },
+                ),
+            };
+
+            Some(Setting {
+                scope: command_name.unwrap_or("global").to_string(),
+                name: arg.get_long().unwrap_or_default().to_string(),
+                value,
+                source,
+            })
+        })
+        .collect();
+
+    // options with a value of the config file are moved to the end, when the default value is set
+    settings.sort_by(|a, b| a.name.cmp(&b.name));
+    settings
+}
+
+// the keys of the config file are the long names of the options, 'no_color' is accepted as well as 'no-color'
+fn option_name(key: &str) -> String {
+    key.replace('_', "-")
+}
+
+fn config_values(value: &Value) -> Result<Vec<String>> {
+    match value {
+        Value::Array(values) => values.iter().map(config_value).collect(),
+        value => Ok(vec![config_value(value)?]),
+    }
+}
+
+fn config_value(value: &Value) -> Result<String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        _ => anyhow::bail!("only strings, numbers, booleans and lists of them are supported"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::http_client;
+    use clap::FromArgMatches;
+
+    fn parse(content: &str, args: &[&str]) -> Result<Cli> {
+        parse_with_env(content, &[], args)
+    }
+
+    fn parse_with_env(content: &str, env: &[(&str, &str)], args: &[&str]) -> Result<Cli> {
+        let config = Config {
+            path: PathBuf::from(CONFIG_FILE_NAME),
+            table: content.parse::<Table>()?,
+        };
+        let command = apply_env(config.apply(Cli::command())?, &test_env(env));
+        let matches = get_matches_from(command, args.iter().map(OsString::from).collect())?;
+        Ok(Cli::from_arg_matches(&matches)?)
+    }
+
+    fn test_env(env: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        move |name| {
+            env.iter()
+                .find(|(env_name, _)| *env_name == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_apply_config() {
+        let content = "no-color = true\n\
+                       [download]\n\
+                       algorithm = \"sha2-512\"\n\
+                       connect_timeout = 30\n\
+                       header = [\"X-Api-Key: 123\"]\n\
+                       [local]\n\
+                       algorithm = \"sha3-384\"\n";
+
+        let cli = parse(content, &["hashguard", "download", "https://example.com"]).unwrap();
+        assert!(cli.no_color);
+        let crate::cli::Command::Download(args) = cli.command else {
+            panic!("download command expected");
+        };
+        assert_eq!(args.algorithm.to_string(), "SHA2-512");
+        assert_eq!(args.http.connect_timeout, 30);
+        assert_eq!(args.http.headers.len(), 1);
+
+        // options on the command line take precedence
+        let cli = parse(
+            content,
+            &["hashguard", "local", "-b", "a", "-a", "sha3-256"],
+        )
+        .unwrap();
+        let crate::cli::Command::Local(args) = cli.command else {
+            panic!("local command expected");
+        };
+        assert_eq!(args.algorithm.to_string(), "SHA3-256");
+
+        assert!(
+            parse(
+                "[download]\nunknown = 1",
+                &["hashguard", "local", "-b", "a"]
+            )
+            .is_err()
+        );
+        assert!(parse("download = 1", &["hashguard", "local", "-b", "a"]).is_err());
+    }
+
+    #[test]
+    fn test_env_var_precedence() {
+        let content = "[download]\n\
+                       connections = 2\n\
+                       [local]\n\
+                       include-names = false\n";
+        let download =
+            |env: &[(&str, &str)], args: &[&str]| match parse_with_env(content, env, args)
+                .unwrap()
+                .command
+            {
+                crate::cli::Command::Download(args) => args,
+                _ => panic!("download command expected"),
+            };
+        let url_args = ["hashguard", "download", "https://example.com"];
+
+        assert_eq!(download(&[], &url_args).connections, 2);
+
+        // the environment takes precedence over the config file
+        let env = [
+            ("HASHGUARD_CONNECTIONS", "4"),
+            ("HASHGUARD_INCLUDE_NAMES", "yes"),
+        ];
+        assert_eq!(download(&env, &url_args).connections, 4);
+        let cli = parse_with_env(content, &env, &["hashguard", "local", "-b", "a"]).unwrap();
+        let crate::cli::Command::Local(args) = cli.command else {
+            panic!("local command expected");
+        };
+        assert!(args.include_names);
+
+        // the command line takes precedence over the environment
+        let args = download(&env, &[&url_args[..], &["--connections", "8"]].concat());
+        assert_eq!(args.connections, 8);
+
+        let env = [("HASHGUARD_CONNECTIONS", "0")];
+        assert!(parse_with_env(content, &env, &url_args).is_err());
+    }
+
+    #[test]
+    fn test_env_var_conflicts() {
+        // an option of the environment does not require another option
+        let env = [("HASHGUARD_NAMESPACE", "x")];
+        assert!(parse_with_env("", &env, &["hashguard", "local", "-b", "a"]).is_ok());
+
+        // a conflicting option on the command line takes precedence
+        let env = [("HASHGUARD_VERBOSE", "1")];
+        let cli = parse_with_env("", &env, &["hashguard", "-q", "local", "-b", "a"]).unwrap();
+        assert!(cli.quiet && !cli.verbose);
+
+        let env = [("HASHGUARD_MAX_REDIRECTS", "3")];
+        let args = [
+            "hashguard",
+            "download",
+            "https://example.com",
+            "--no-redirect",
+        ];
+        let cli = parse_with_env("", &env, &args).unwrap();
+        let crate::cli::Command::Download(args) = cli.command else {
+            panic!("download command expected");
+        };
+        assert!(args.http.no_redirect);
+        assert_eq!(args.http.max_redirects, http_client::DEFAULT_MAX_REDIRECTS);
+
+        // the same applies to the config file
+        let cli = parse("quiet = true", &["hashguard", "-v", "local", "-b", "a"]).unwrap();
+        assert!(cli.verbose && !cli.quiet);
+    }
+
+    #[test]
+    fn test_env_name() {
+        let command = Cli::command()
+            .arg(Arg::new("help").long("help").action(ArgAction::Help))
+            .arg(
+                Arg::new("version")
+                    .long("version")
+                    .action(ArgAction::Version),
+            );
+
+        let env_names: Vec<_> = command.get_arguments().filter_map(env_name).collect();
+        assert!(env_names.contains(&"HASHGUARD_NO_COLOR".to_string()));
+        assert!(!env_names.contains(&"HASHGUARD_HELP".to_string()));
+        assert!(!env_names.contains(&"HASHGUARD_VERSION".to_string()));
+
+        let settings = settings(None, &test_env(&[("HASHGUARD_CONNECTIONS", "4")])).unwrap();
+        let setting = settings
+            .iter()
+            .find(|setting| setting.name == "connections")
+            .unwrap();
+        assert_eq!(setting.value, "4");
+        assert_eq!(
+            setting.source,
+            SettingSource::Environment("HASHGUARD_CONNECTIONS".to_string())
+        );
+    }
+}
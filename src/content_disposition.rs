@@ -0,0 +1,322 @@
+use percent_encoding::percent_decode_str;
+
+/// Parameters of a `Content-Disposition` header (RFC 6266)
+#[derive(Debug, Default, PartialEq)]
+pub struct ContentDisposition {
+    /// The disposition type, e.g. `attachment` or `inline`
+    pub disposition: String,
+    parameters: Vec<(String, String)>,
+}
+
+impl ContentDisposition {
+    /// Parses the header value, parameters with invalid syntax are skipped
+    pub fn parse(header_value: &str) -> Self {
+        let mut parser = Parser {
+            input: header_value,
+            pos: 0,
+        };
+
+        parser.skip_whitespace();
+        let start = parser.pos;
+        let disposition = parser.token();
+
+        // some servers omit the disposition type, e.g. 'filename="file.txt"'
+        let (disposition, mut has_parameter) = if parser.peek() == Some('=') {
+            parser.pos = start;
+            (String::new(), true)
+        } else {
+            (disposition.to_lowercase(), parser.skip_to_next_parameter())
+        };
+
+        let mut parameters = vec![];
+
+        while has_parameter {
+            let name = parser.token().to_lowercase();
+            parser.skip_whitespace();
+
+            if !name.is_empty() && parser.consume('=') {
+                parser.skip_whitespace();
+                let value = match parser.peek() {
+                    Some('"') => parser.quoted_string(),
+                    _ => parser.value_until_separator(),
+                };
+
+                parameters.push((name, value));
+            }
+
+            has_parameter = parser.skip_to_next_parameter();
+        }
+
+        Self {
+            disposition,
+            parameters,
+        }
+    }
+
+    /// Returns the value of the first parameter with the given (lowercase) name
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(parameter_name, _)| parameter_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the file name, `filename*` (RFC 8187) takes precedence over `filename`.
+    /// Directory parts of the name are removed.
+    pub fn file_name(&self) -> Option<String> {
+        let ext_file_name = self.parameter("filename*").and_then(decode_ext_value);
+
+        let file_name = ext_file_name.or_else(|| {
+            self.parameter("filename").map(|file_name| {
+                // not allowed by RFC 6266, but some servers percent-encode the plain file name
+                let file_name = file_name.trim_matches('\'');
+                percent_decode_str(file_name)
+                    .decode_utf8()
+                    .map(|decoded| decoded.to_string())
+                    .unwrap_or_else(|_| file_name.to_string())
+            })
+        })?;
+
+        // a file name must not point into another directory
+        let file_name = file_name
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        if file_name.is_empty() {
+            None
+        } else {
+            Some(file_name)
+        }
+    }
+}
+
+/// Decodes an extended parameter value (RFC 8187), e.g. `UTF-8'en'%E2%82%AC%20rates.txt`.
+/// Only the charsets UTF-8 and ISO-8859-1 are supported.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?.trim().to_lowercase();
+    let _language = parts.next()?;
+    let encoded = parts.next()?;
+
+    let bytes: Vec<u8> = percent_decode_str(encoded).collect();
+
+    match charset.as_str() {
+        "utf-8" => String::from_utf8(bytes).ok(),
+        // each byte of ISO-8859-1 is the Unicode code point of the char
+        "iso-8859-1" => Some(bytes.into_iter().map(char::from).collect()),
+        _ => {
+            log::debug!("Unsupported charset of Content-Disposition parameter: {charset}");
+            None
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(char) = self.peek().filter(|char| char.is_whitespace()) {
+            self.pos += char.len_utf8();
+        }
+    }
+
+    /// Moves behind the next ';', returns `false` at the end of the input
+    fn skip_to_next_parameter(&mut self) -> bool {
+        match self.input[self.pos..].find(';') {
+            Some(idx) => {
+                self.pos += idx + 1;
+                self.skip_whitespace();
+                true
+            }
+            None => {
+                self.pos = self.input.len();
+                false
+            }
+        }
+    }
+
+    fn token(&mut self) -> String {
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|char: char| char == ';' || char == '=' || char.is_whitespace())
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].to_string()
+    }
+
+    fn value_until_separator(&mut self) -> String {
+        let rest = &self.input[self.pos..];
+        let len = rest.find(';').unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].trim().to_string()
+    }
+
+    /// Reads a quoted-string, a backslash escapes the next char
+    fn quoted_string(&mut self) -> String {
+        let mut value = String::new();
+        let mut chars = self.input[self.pos..].char_indices().skip(1);
+
+        while let Some((idx, char)) = chars.next() {
+            match char {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        value.push(escaped);
+                    }
+                }
+                '"' => {
+                    self.pos += idx + 1;
+                    return value;
+                }
+                _ => value.push(char),
+            }
+        }
+
+        // the closing quote is missing
+        self.pos = self.input.len();
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file_name(header_value: &str) -> Option<String> {
+        ContentDisposition::parse(header_value).file_name()
+    }
+
+    #[test]
+    fn test_parameter_names() {
+        let content_disposition =
+            ContentDisposition::parse("Attachment; FileName=example.txt; size=42; size=1");
+        assert_eq!(content_disposition.disposition, "attachment");
+        assert_eq!(content_disposition.parameter("size"), Some("42"));
+        assert_eq!(
+            content_disposition.file_name(),
+            Some("example.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_disposition_type() {
+        let content_disposition = ContentDisposition::parse("INLINE; filename=\"example.txt\"");
+        assert_eq!(content_disposition.disposition, "inline");
+        assert_eq!(
+            content_disposition.file_name(),
+            Some("example.txt".to_string())
+        );
+
+        // header without disposition type
+        assert_eq!(
+            file_name("filename=\"example.txt\""),
+            Some("example.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_quoted_string() {
+        assert_eq!(
+            file_name(r#"attachment; filename="semi;colon \"quoted\".txt""#),
+            Some("semi;colon \"quoted\".txt".to_string())
+        );
+        assert_eq!(
+            file_name("attachment; filename=\"utf-8 manual.pdf\""),
+            Some("utf-8 manual.pdf".to_string())
+        );
+        assert_eq!(file_name("attachment; filename=\"\""), None);
+    }
+
+    #[test]
+    fn test_ext_value() {
+        assert_eq!(
+            file_name("attachment; filename*=UTF-8''%E2%82%AC%20rates.txt"),
+            Some("€ rates.txt".to_string())
+        );
+        assert_eq!(
+            file_name("attachment; filename*=utf-8'en'example.txt"),
+            Some("example.txt".to_string())
+        );
+        assert_eq!(
+            file_name("attachment; filename*=iso-8859-1'de'f%E4rbung.txt"),
+            Some("färbung.txt".to_string())
+        );
+        // invalid UTF-8
+        assert_eq!(file_name("attachment; filename*=UTF-8''%E4.txt"), None);
+    }
+
+    #[test]
+    fn test_precedence() {
+        // filename* is preferred, independent of the order
+        assert_eq!(
+            file_name(
+                "attachment; filename=\"EURO rates.txt\"; filename*=utf-8''%e2%82%ac%20rates.txt"
+            ),
+            Some("€ rates.txt".to_string())
+        );
+        assert_eq!(
+            file_name(
+                "attachment; filename*=UTF-8''%e2%82%ac%20rates.txt; filename=\"EURO rates.txt\""
+            ),
+            Some("€ rates.txt".to_string())
+        );
+        // an unsupported charset falls back to filename
+        assert_eq!(
+            file_name("attachment; filename*=koi8-r''%F0.txt; filename=\"fallback.txt\""),
+            Some("fallback.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_real_world_headers() {
+        // GitHub release assets
+        assert_eq!(
+            file_name("attachment; filename=hashguard-x86_64-unknown-linux-gnu.tar.gz"),
+            Some("hashguard-x86_64-unknown-linux-gnu.tar.gz".to_string())
+        );
+        // Amazon S3 / CloudFront
+        assert_eq!(
+            file_name(
+                "attachment; filename=\"report 2024.pdf\"; filename*=UTF-8''report%202024.pdf"
+            ),
+            Some("report 2024.pdf".to_string())
+        );
+        // Django / Nextcloud
+        assert_eq!(
+            file_name("attachment; filename*=UTF-8''Stra%C3%9Fe.zip; filename=\"Strasse.zip\""),
+            Some("Straße.zip".to_string())
+        );
+        // percent-encoded plain file name
+        assert_eq!(
+            file_name("attachment; filename=\"Na%C3%AFve%20file.txt\""),
+            Some("Naïve file.txt".to_string())
+        );
+        // directory parts are removed
+        assert_eq!(
+            file_name("attachment; filename=\"../../etc/passwd\""),
+            Some("passwd".to_string())
+        );
+        assert_eq!(
+            file_name("attachment; filename=\"C:\\\\temp\\\\setup.exe\""),
+            Some("setup.exe".to_string())
+        );
+    }
+}
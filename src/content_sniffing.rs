@@ -0,0 +1,96 @@
+use std::path::Path;
+
+// Extensions of files, which are expected to contain an HTML page
+const HTML_EXTENSIONS: [&str; 8] = ["html", "htm", "xhtml", "shtml", "php", "asp", "aspx", "jsp"];
+
+// Tags, which start an HTML page (compared case-insensitive)
+const HTML_START_TAGS: [&[u8]; 5] = [b"<!doctype html", b"<html", b"<head", b"<body", b"<script"];
+
+/// Checks whether the content starts like an HTML page, a leading BOM, white spaces and comments are skipped
+pub fn is_html(content_prefix: &[u8]) -> bool {
+    let mut content = content_prefix
+        .strip_prefix(b"\xef\xbb\xbf".as_slice())
+        .unwrap_or(content_prefix);
+
+    loop {
+        content = content.trim_ascii_start();
+
+        match content.strip_prefix(b"<!--".as_slice()) {
+            Some(comment) => match comment.windows(3).position(|window| window == b"-->") {
+                Some(end) => content = &comment[end + 3..],
+                None => return false,
+            },
+            None => break,
+        }
+    }
+
+    HTML_START_TAGS.iter().any(|tag| {
+        content
+            .get(..tag.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(tag))
+    })
+}
+
+/// Checks whether an HTML page arrived instead of the expected file, e.g. the login page of a captive portal.
+/// The content is suspicious if the file name has an extension of another type,
+/// or the `Content-Type` announces another type than HTML.
+pub fn is_unexpected_html(
+    file_name: &str,
+    content_type: Option<&str>,
+    content_prefix: &[u8],
+) -> bool {
+    if !is_html(content_prefix) {
+        return false;
+    }
+
+    let other_extension = Path::new(file_name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| !HTML_EXTENSIONS.contains(&extension.as_str()));
+
+    let other_content_type = content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|media_type| media_type.trim().to_lowercase())
+        .is_some_and(|media_type| {
+            !media_type.is_empty() && media_type != "text/html" && media_type != "text/plain"
+        });
+
+    other_extension || other_content_type
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_unexpected_html() {
+        let login_page =
+            b"\xef\xbb\xbf\n  <!-- portal --><!DOCTYPE HTML><html><head><title>Login</title>";
+
+        assert!(is_html(login_page));
+        assert!(!is_html(b"MZ\x90\x00"));
+        assert!(!is_html(b"<!-- unterminated"));
+
+        assert!(is_unexpected_html(
+            "installer.exe",
+            Some("text/html; charset=utf-8"),
+            login_page
+        ));
+        assert!(is_unexpected_html(
+            "download",
+            Some("application/octet-stream"),
+            login_page
+        ));
+        assert!(!is_unexpected_html(
+            "index.html",
+            Some("text/html"),
+            login_page
+        ));
+        assert!(!is_unexpected_html("download", None, login_page));
+        assert!(!is_unexpected_html(
+            "installer.exe",
+            Some("text/html"),
+            b"MZ\x90\x00"
+        ));
+    }
+}
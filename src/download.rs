@@ -1,24 +1,44 @@
 use std::{
     cmp::min,
     error::Error,
-    fs::File,
-    io::{BufWriter, Read, Write},
-    path::PathBuf,
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
     time::{Duration, Instant},
 };
 
 use crate::{
+    compression::{self, CompressionFormat},
+    content_sniffing,
+    download_cache::{self, CacheEntry},
     filename_handling,
-    hasher::{Algorithm, Hasher},
+    hasher::{self, Algorithm, Hasher},
+    http_client::{self, HttpClient, HttpOptions, RedirectChain},
+    integrity::{DigestVerifier, IntegrityCheck},
+    local,
     os_specifics::OS,
     term_output, utils,
 };
 use anyhow::Result;
-use ureq::{ResponseExt, config::Config, http::header::*};
+use base64::Engine;
+use clap::ValueEnum;
+use ureq::{
+    Body, ResponseExt,
+    http::{HeaderMap, Response, StatusCode, header::*},
+};
+use url::Url;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Default number of connections per download
+pub const DEFAULT_CONNECTIONS: u8 = 1;
 
-use indicatif::{ProgressBar, ProgressStyle};
+// Files are only split into segments of at least 1 MiB
+const MIN_SEGMENT_SIZE: u64 = 1024 * 1024;
 
-const CONNECTION_TIMEOUT: Duration = Duration::from_secs(25);
+// Number of bytes at the start of the content, which are used to detect the type of the content
+const CONTENT_PREFIX_LEN: usize = 512;
 
 /// Error type for download operations
 #[derive(Debug, Clone)]
@@ -40,19 +60,149 @@ impl std::fmt::Display for DownloadError {
     }
 }
 
+/// Error type for a response body whose size differs from the size announced by the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BodySizeError {
+    /// The connection was closed before all bytes were received
+    Truncated { expected: usize, received: usize },
+    /// The server sent more bytes than announced
+    Overlong { expected: usize },
+}
+
+impl Error for BodySizeError {}
+
+impl std::fmt::Display for BodySizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodySizeError::Truncated { expected, received } => write!(
+                f,
+                "Download incomplete | The server response was truncated - Received {received} of {expected} bytes"
+            ),
+            BodySizeError::Overlong { expected } => write!(
+                f,
+                "Download incomplete | The server response exceeds the announced file size of {expected} bytes"
+            ),
+        }
+    }
+}
+
+/// Order in which the URLs of a file are tried
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum MirrorOrder {
+    /// try the URLs in the given order
+    #[default]
+    InOrder,
+    /// try the URL with the shortest response time first
+    Fastest,
+}
+
+/// Defines what happens with the downloaded data
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// save the file in the output target
+    #[default]
+    File,
+    /// only calculate the hash sum, nothing is saved
+    Discard,
+    /// write the data to the standard output, e.g. to pipe it into another program
+    Stdout,
+}
+
 #[derive(Debug)]
 pub struct DownloadProperties {
     pub algorithm: Algorithm,
     pub url: String,
+    /// Alternative URLs of the same file, which are tried if the download from the previous URL fails
+    pub mirrors: Vec<String>,
+    pub mirror_order: MirrorOrder,
+    /// If the hash of a downloaded file does not match, the next mirror is tried
+    pub expected_hash: Option<String>,
+    /// If the size of a downloaded file does not match, the download fails
+    pub expected_size: Option<u64>,
     pub output_target: PathBuf,
+    pub output_mode: OutputMode,
     pub default_file_name: Option<String>,
+    /// If `false`, the user is never asked for input, e.g. for a missing file name
+    pub interactive: bool,
     pub os_type: OS,
+    /// Max. number of concurrent connections, which download a segment of the file
+    pub connections: u8,
+    /// Max. download rate in bytes per second
+    pub limit_rate: Option<u64>,
+    /// Reuse the file of a previous download, if the server reports that it is unchanged
+    pub use_cache: bool,
+    /// Hash and save the server response as it was transferred, without decoding the Content-Encoding
+    pub hash_raw: bool,
+    /// Decompress a downloaded .gz, .xz or .zst file
+    pub decompress: bool,
+    /// Append an extension derived from the Content-Type or the content to a file name without extension
+    pub infer_extension: bool,
+    /// Fail instead of warning, if an HTML page arrives instead of the expected file
+    pub strict: bool,
+    /// Hide the progress bars and informational messages, warnings are printed to the standard error
+    pub quiet: bool,
+    pub http_options: HttpOptions,
+    /// Set for batch downloads: all progress bars are attached to it and the user is never prompted
+    pub multi_progress: Option<MultiProgress>,
 }
 
 #[derive(Debug)]
 pub struct DownloadResult {
-    pub file_location: PathBuf,
+    /// Location of the saved file, `None` if the file was not saved
+    pub file_location: Option<PathBuf>,
     pub hash_sum: String,
+    /// The URL from which the file was downloaded
+    pub source_url: String,
+    /// The results of the comparison with the digests advertised by the server
+    pub integrity_checks: Vec<IntegrityCheck>,
+    /// All URLs of the request incl. the final URL, if the server redirected the request
+    pub redirect_chain: Vec<String>,
+    /// `true` if the file of a previous download was reused, because it is unchanged
+    pub from_cache: bool,
+    /// Size of the hashed data in bytes
+    pub size: u64,
+    /// Duration of the download incl. the hash calculation
+    pub elapsed: Duration,
+    /// The URL of the response after all redirects, `None` for local sources
+    pub final_url: Option<String>,
+    /// The headers of the server response
+    pub response_headers: Vec<(String, String)>,
+}
+
+/// Limits the download rate, a throttle is shared by all connections of a download
+struct Throttle {
+    /// Max. bytes per second
+    rate: u64,
+    start: Instant,
+    received_bytes: AtomicU64,
+}
+
+impl Throttle {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            start: Instant::now(),
+            received_bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Max. number of bytes of a single read, so that the rate is evenly limited
+    fn chunk_size(&self) -> usize {
+        (self.rate / 10).clamp(1, utils::CAPACITY as u64) as usize
+    }
+
+    /// Counts the received bytes and waits until the download rate is below the limit
+    fn consume(&self, bytes: usize) {
+        let received_bytes = self
+            .received_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed)
+            + bytes as u64;
+        let target_duration = Duration::from_secs_f64(received_bytes as f64 / self.rate as f64);
+
+        if let Some(delay) = target_duration.checked_sub(self.start.elapsed()) {
+            std::thread::sleep(delay);
+        }
+    }
 }
 
 /// Enum to hold the state of the file size
@@ -80,17 +230,494 @@ enum FileSizeState {
 //     }
 // }
 
+/// Executes the file download for the specified URL and its mirrors and returns the path where the file was saved.
+///
+/// The URLs are tried one after another until a download succeeds. If an expected hash is given,
+/// a downloaded file with another hash is removed and the next mirror is tried.
+pub fn execute_download(download_properties: DownloadProperties) -> Result<DownloadResult> {
+    let mut urls = vec![download_properties.url.clone()];
+    urls.extend(download_properties.mirrors.iter().cloned());
+
+    let http_client = HttpClient::new(&download_properties.http_options)?;
+
+    if urls.len() > 1 && download_properties.mirror_order == MirrorOrder::Fastest {
+        urls = sort_by_response_time(&http_client, urls);
+    }
+
+    // all mirrors must save the file under the same name
+    let mut file_name = download_properties.default_file_name.clone();
+
+    for (idx, url) in urls.iter().enumerate() {
+        let is_last_url = idx + 1 == urls.len();
+        // credentials of the URL must not be written to the log file
+        let log_url = http_client::redact_url(url);
+
+        if urls.len() > 1 {
+            log::info!(
+                "Try to download from mirror {}/{}: {log_url}",
+                idx + 1,
+                urls.len()
+            );
+        }
+
+        let download_result =
+            match download_from_url(&http_client, url, &download_properties, file_name.clone()) {
+                Ok(download_result) => download_result,
+                Err(download_err) if !is_last_url => {
+                    log::warn!("Download from mirror '{log_url}' failed - Try next mirror");
+                    print_message(
+                        &download_properties,
+                        format!("{download_err}\nTry next mirror..."),
+                    );
+                    continue;
+                }
+                Err(download_err) => return Err(download_err),
+            };
+
+        if file_name.is_none() {
+            file_name = download_result
+                .file_location
+                .as_deref()
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().to_string());
+        }
+
+        match &download_properties.expected_hash {
+            Some(expected_hash)
+                if !is_last_url
+                    && !hasher::is_hash_equal(expected_hash, &download_result.hash_sum) =>
+            {
+                log::warn!(
+                    "The file from mirror '{log_url}' does not match the given hash - Try next mirror"
+                );
+                print_message(
+                    &download_properties,
+                    format!(
+                        "The file from '{log_url}' does not match the given hash\nTry next mirror..."
+                    ),
+                );
+                if let Some(file_location) = &download_result.file_location
+                    && let Err(io_err) = std::fs::remove_file(file_location)
+                {
+                    log::error!(
+                        "Failed to remove file: {} - Details: {io_err:?}",
+                        utils::absolute_path_as_string(file_location)
+                    );
+                }
+            }
+            _ => {
+                if urls.len() > 1 {
+                    log::info!("File was downloaded from mirror: {log_url}");
+                }
+                return Ok(download_result);
+            }
+        }
+    }
+
+    // unreachable, because the last URL always returns
+    Err(DownloadError::new("No URL to download from".to_string()).into())
+}
+
+/// Sorts the URLs by the response time of a HEAD request, unreachable URLs are moved to the end
+fn sort_by_response_time(http_client: &HttpClient, urls: Vec<String>) -> Vec<String> {
+    let mut response_times: Vec<(Duration, String)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = urls
+            .into_iter()
+            .map(|url| {
+                scope.spawn(move || {
+                    let start = Instant::now();
+                    let response_time = match http_client.head(&url) {
+                        Ok(_) => start.elapsed(),
+                        Err(_) => Duration::MAX,
+                    };
+                    log::debug!(
+                        "Response time of mirror '{}': {response_time:?}",
+                        http_client::redact_url(&url)
+                    );
+                    (response_time, url)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
+    });
+
+    response_times.sort_by_key(|(response_time, _)| *response_time);
+    response_times.into_iter().map(|(_, url)| url).collect()
+}
+
+/// Prints a message without disturbing the progress bars of a batch download.
+/// The message is printed to the standard error, if the standard output only contains the data or the hash.
+fn print_message(download_properties: &DownloadProperties, msg: String) {
+    match &download_properties.multi_progress {
+        Some(multi_progress) => {
+            let _ = multi_progress.println(msg);
+        }
+        None if download_properties.quiet
+            || download_properties.output_mode == OutputMode::Stdout =>
+        {
+            eprintln!("{msg}")
+        }
+        None => println!("{msg}"),
+    }
+}
+
 /// Executes the file download for the specified URL and returns the path where the file was saved
 /// * Make a HTTP-GET request
 /// * Check the server response for errors
 /// * Verify the response for the required HTTP headers
 /// * Starts a progress bar to display the download progress
 /// * Write all bytes from the HTTP response body to a file in 4KiB blocks
-pub fn execute_download(download_properties: DownloadProperties) -> Result<DownloadResult> {
+fn download_from_url(
+    http_client: &HttpClient,
+    url: &str,
+    download_properties: &DownloadProperties,
+    default_file_name: Option<String>,
+) -> Result<DownloadResult> {
+    if utils::is_local_url(url) {
+        return download_from_local_url(url, download_properties, default_file_name);
+    }
+
+    let start = Instant::now();
+
+    // a previous download can be reused, if the server reports that the file is unchanged
+    let cache_entry = cached_download(url, download_properties, default_file_name.as_deref());
+    let conditions = cache_entry
+        .as_ref()
+        .map(CacheEntry::conditional_headers)
+        .unwrap_or_default();
+
+    let mut response = send_request(http_client, url, conditions, download_properties)?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(cache_entry) = cache_entry
+            && let Some(download_result) =
+                reuse_cached_download(url, cache_entry, &response, download_properties)?
+        {
+            return Ok(download_result);
+        }
+
+        // the cached file can not be reused -> request the file again
+        response = send_request(http_client, url, HeaderMap::new(), download_properties)?;
+    }
+
+    // the body is decoded while it is read, unless the raw data should be hashed
+    let response_coding = compression::content_coding(response.headers());
+    let content_coding = response_coding
+        .as_ref()
+        .filter(|_| !download_properties.hash_raw);
+
+    if let Some(coding) = &response_coding {
+        log::info!(
+            "The server response is encoded ({coding}) - The {} data is hashed",
+            if content_coding.is_some() {
+                "decoded"
+            } else {
+                "raw"
+            }
+        );
+    }
+
+    // the Content-Length refers to the encoded data
+    let file_size_state = match content_coding {
+        Some(_) => FileSizeState::Unknown,
+        None => determine_file_size_state(response.headers()),
+    };
+
+    check_announced_size(&file_size_state, download_properties.expected_size)?;
+
+    // IMPORTANT: use the url from the response object, because in case of an redirect the
+    // url can differ from the request url when the http client has follows redirects.
+    let uri = response.get_uri().to_string();
+
+    let redirect_chain = redirect_chain_of(&response);
+    let response_headers = response.headers().clone();
+
+    // get the Content-Disposition header
+    let content_disposition = response
+        .headers()
+        .get(CONTENT_DISPOSITION)
+        .map_or("", |header_value| header_value.to_str().unwrap_or_default())
+        .to_string();
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|header_value| header_value.to_str().ok())
+        .map(str::to_string);
+
+    // the file name may depend on the first bytes of the content, so the path is built after they are read
+    let resolve_file_path = |content_prefix: &[u8]| {
+        let extract_result = match &default_file_name {
+            Some(default_file_name) => Some(default_file_name.clone()),
+            None => {
+                // if the user has not specified a default filename via the --rename option
+                // -> try to extract the filename from the server response
+                utils::extract_file_name(&uri, &content_disposition, &download_properties.os_type)
+                    .map(|file_name| match download_properties.infer_extension {
+                        true => filename_handling::with_inferred_extension(
+                            &download_properties.os_type,
+                            file_name,
+                            content_type.as_deref(),
+                            content_prefix,
+                        ),
+                        false => file_name,
+                    })
+            }
+        };
+
+        build_file_path(
+            url,
+            extract_result,
+            content_type.as_deref(),
+            download_properties,
+        )
+    };
+
+    // digests of the file advertised by the server, which are verified during the download
+    let mut digest_verifier =
+        DigestVerifier::from_headers(response.headers(), download_properties.hash_raw);
+
+    // the segments are written at their position into the file, so a file is required.
+    // Segments are requested without encoding, so the response must not be encoded either.
+    let segments = (download_properties.output_mode == OutputMode::File
+        && response_coding.is_none())
+    .then(|| {
+        plan_segments(
+            &file_size_state,
+            response.headers(),
+            download_properties.connections,
+        )
+    })
+    .flatten();
+
+    let download_result = match segments {
+        Some(segments) => {
+            let content_prefix = peek_body(&mut response);
+
+            // the segments are requested separately, so the current response is no longer needed
+            drop(response);
+
+            let file_path = resolve_file_path(&content_prefix)?;
+            check_content(
+                &file_name_of(&file_path),
+                content_type.as_deref(),
+                &content_prefix,
+                download_properties,
+            )?;
+
+            // custom headers and credentials are only sent to the origin of the URL,
+            // so the segments are requested via the given URL, if the server redirected to another origin
+            let segment_url = if http_client::is_same_origin(url, &uri) {
+                &uri
+            } else {
+                url
+            };
+
+            make_segmented_download_req(
+                http_client,
+                segment_url,
+                file_path,
+                &segments,
+                download_properties,
+                &mut digest_verifier,
+            )
+            .map(|(file_location, hash_sum)| {
+                // the segments cover the whole file
+                let size = segments.last().map_or(0, |(_, end)| end + 1);
+                (Some(file_location), hash_sum, size)
+            })
+        }
+        None => {
+            // capture the server response body and turn it into a Reader
+            let body_reader = response.into_body().into_reader();
+            let body_reader: Box<dyn Read> = match content_coding {
+                Some(coding) => compression::decode_content(body_reader, coding)?,
+                None => Box::new(body_reader),
+            };
+            let (body_reader, content_prefix) = read_body_prefix(body_reader, &file_size_state)?;
+
+            let file_path = match download_properties.output_mode {
+                OutputMode::File => Some(resolve_file_path(&content_prefix)?),
+                OutputMode::Discard | OutputMode::Stdout => None,
+            };
+
+            let file_name = match &file_path {
+                Some(file_path) => file_name_of(file_path),
+                None => utils::extract_file_name(
+                    &uri,
+                    &content_disposition,
+                    &download_properties.os_type,
+                )
+                .unwrap_or_default(),
+            };
+            check_content(
+                &file_name,
+                content_type.as_deref(),
+                &content_prefix,
+                download_properties,
+            )?;
+
+            // start the download process
+            make_download_req(
+                file_path,
+                body_reader,
+                file_size_state,
+                download_properties,
+                &mut digest_verifier,
+            )
+        }
+    };
+
+    let (file_location, hash_sum, size) = download_result?;
+
+    if let (Some(file_location), Some(expected_size)) =
+        (&file_location, download_properties.expected_size)
+    {
+        verify_file_size(file_location, expected_size)?;
+    }
+
+    let (file_location, hash_sum) = match file_location {
+        Some(file_location) if download_properties.decompress => {
+            let (file_location, hash_sum) =
+                decompress_download(file_location, hash_sum, download_properties)?;
+            (Some(file_location), hash_sum)
+        }
+        file_location => (file_location, hash_sum),
+    };
+    let size = hashed_size(file_location.as_deref(), size);
+
+    // only a verified file is cached, a file that does not match the given hash is downloaded again.
+    // A decompressed file can not be compared with the validators of the compressed file.
+    if let Some(file_location) = &file_location
+        && download_properties.use_cache
+        && !download_properties.decompress
+        && download_properties
+            .expected_hash
+            .as_ref()
+            .is_none_or(|expected_hash| hasher::is_hash_equal(expected_hash, &hash_sum))
+    {
+        cache_download(
+            url,
+            &response_headers,
+            file_location,
+            &hash_sum,
+            download_properties,
+        );
+    }
+
+    Ok(DownloadResult {
+        file_location,
+        hash_sum,
+        source_url: url.to_string(),
+        integrity_checks: digest_verifier.finalize(),
+        redirect_chain,
+        from_cache: false,
+        size,
+        elapsed: start.elapsed(),
+        final_url: Some(http_client::redact_url(&uri)),
+        response_headers: header_list(&response_headers),
+    })
+}
+
+/// Warns, or fails in strict mode, if an HTML page arrives instead of the expected file,
+/// e.g. the login page of a captive portal, which is sent with status 200
+fn check_content(
+    file_name: &str,
+    content_type: Option<&str>,
+    content_prefix: &[u8],
+    download_properties: &DownloadProperties,
+) -> Result<()> {
+    if !content_sniffing::is_unexpected_html(file_name, content_type, content_prefix) {
+        return Ok(());
+    }
+
+    let msg = format!(
+        "The server sent an HTML page instead of the expected file '{file_name}' (Content-Type: {}), e.g. a login page or an error page",
+        content_type.unwrap_or("none")
+    );
+
+    if download_properties.strict {
+        let download_err = DownloadError::new(msg);
+        log::error!("{download_err}");
+        return Err(download_err.into());
+    }
+
+    log::warn!("{msg}");
+    print_message(
+        download_properties,
+        format!("Warning: {msg}. Use the option [--strict] to cancel such downloads"),
+    );
+
+    Ok(())
+}
+
+/// Reads the first bytes of the response body, which reveal the type of the content.
+/// Returns a reader, which still yields the complete body.
+fn read_body_prefix(
+    mut body_reader: Box<dyn Read>,
+    file_size_state: &FileSizeState,
+) -> Result<(Box<dyn Read>, Vec<u8>)> {
+    let mut content_prefix = Vec::with_capacity(CONTENT_PREFIX_LEN);
+
+    if let Err(body_access_err) = body_reader
+        .by_ref()
+        .take(CONTENT_PREFIX_LEN as u64)
+        .read_to_end(&mut content_prefix)
+    {
+        return match *file_size_state {
+            // the connection was closed before the announced number of bytes were received
+            FileSizeState::Known(total_size)
+                if body_access_err.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                log::debug!("Details: {body_access_err:?}");
+                Err(truncated_error(total_size, content_prefix.len()))
+            }
+            _ => {
+                let download_err =
+                    DownloadError::new("Failed to read data from server response".to_string());
+                log::error!("{download_err} - Details: {body_access_err:?}");
+                Err(download_err.into())
+            }
+        };
+    }
+
+    let body_reader = std::io::Cursor::new(content_prefix.clone()).chain(body_reader);
+    Ok((Box::new(body_reader), content_prefix))
+}
+
+/// Reads the first bytes of a response body, which is not downloaded itself, e.g. because the file is downloaded in segments
+fn peek_body(response: &mut Response<Body>) -> Vec<u8> {
+    let mut content_prefix = Vec::with_capacity(CONTENT_PREFIX_LEN);
+
+    if let Err(body_access_err) = response
+        .body_mut()
+        .as_reader()
+        .take(CONTENT_PREFIX_LEN as u64)
+        .read_to_end(&mut content_prefix)
+    {
+        log::debug!(
+            "Failed to read the start of the server response - Details: {body_access_err:?}"
+        );
+    }
+
+    content_prefix
+}
+
+/// Sends the GET request, while a spinner shows the connection establishment
+fn send_request(
+    http_client: &HttpClient,
+    url: &str,
+    request_headers: HeaderMap,
+    download_properties: &DownloadProperties,
+) -> Result<Response<Body>> {
     let spinner = ProgressBar::new_spinner()
         .with_message(format!(
             "Connection establishment... Timeout: {}s",
-            CONNECTION_TIMEOUT.as_secs()
+            download_properties.http_options.connect_timeout.as_secs()
         ))
         .with_position(25);
 
@@ -101,17 +728,12 @@ pub fn execute_download(download_properties: DownloadProperties) -> Result<Downl
             .unwrap_or_else(|_| ProgressStyle::default_spinner()),
     );
 
+    let spinner = attach_progress_bar(download_properties, spinner);
+
     // Set spinner tick every 100ms
     spinner.enable_steady_tick(Duration::from_millis(100));
 
-    let http_agent = Config::builder()
-        .http_status_as_error(true)
-        .save_redirect_history(true)
-        .timeout_connect(Some(CONNECTION_TIMEOUT))
-        .build()
-        .new_agent();
-
-    let response = match http_agent.get(&download_properties.url).call() {
+    let response = match http_client.get_with_headers(url, request_headers) {
         Ok(response) => {
             spinner.finish_and_clear();
             response
@@ -129,79 +751,563 @@ pub fn execute_download(download_properties: DownloadProperties) -> Result<Downl
         }
     };
 
-    let file_size_state = determine_file_size_state(response.headers());
+    Ok(response)
+}
+
+/// Decompresses a downloaded .gz, .xz or .zst file and returns the path and hash sum of the decompressed file.
+/// The compressed file is removed, a file that is not compressed is kept as it is.
+fn decompress_download(
+    file_path: PathBuf,
+    hash_sum: String,
+    download_properties: &DownloadProperties,
+) -> Result<(PathBuf, String)> {
+    let Some(format) = CompressionFormat::detect(&file_path)? else {
+        log::info!(
+            "The file is not compressed: {}",
+            utils::absolute_path_as_string(&file_path)
+        );
+        if !download_properties.quiet {
+            print_message(
+                download_properties,
+                format!(
+                    "The file is not compressed (gzip, xz, zstd) and is saved as downloaded: {}",
+                    file_name_of(&file_path)
+                ),
+            );
+        }
+        return Ok((file_path, hash_sum));
+    };
+
+    log::info!("Hash sum of the compressed file: {hash_sum}");
 
-    if let FileSizeState::Unknown = file_size_state {
-        let err_description = "The server response did not contain any information on how to handle the file size of the file to be downloaded. \
-    Please check the server or try to download the file from another source.";
+    let target = compression::decompressed_path(&file_path, format);
 
-        let download_err = DownloadError::new(err_description.to_string());
+    let spinner = ProgressBar::new_spinner()
+        .with_message(format!("Decompress file... {}", file_name_of(&file_path)));
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .tick_strings(&term_output::BOUNCING_BAR)
+            .template("{spinner:.white} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+    );
+    let spinner = attach_progress_bar(download_properties, spinner);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+
+    let decompress_result =
+        compression::decompress_file(&file_path, &target, format, download_properties.algorithm);
+
+    spinner.finish_and_clear();
+
+    let remove_file = |path: &Path| {
+        if let Err(io_err) = std::fs::remove_file(path) {
+            log::error!(
+                "Failed to remove file: {} - Details: {io_err:?}",
+                utils::absolute_path_as_string(path)
+            );
+        }
+    };
+
+    match decompress_result {
+        Ok(hash_sum) => {
+            remove_file(&file_path);
+            Ok((target, hash_sum))
+        }
+        Err(decompress_err) => {
+            // the compressed file is kept, so that it can be inspected
+            remove_file(&target);
+            Err(decompress_err)
+        }
+    }
+}
+
+/// Returns all URLs of the request incl. the final URL, if the server redirected the request
+fn redirect_chain_of(response: &Response<Body>) -> Vec<String> {
+    let uri = response.get_uri().to_string();
+
+    match response.extensions().get::<RedirectChain>() {
+        Some(RedirectChain(redirects)) if !redirects.is_empty() => {
+            let redirect_chain: Vec<String> = redirects
+                .iter()
+                .chain(std::iter::once(&uri))
+                .map(|url| http_client::redact_url(url))
+                .collect();
+            log::info!("Redirect chain: {}", redirect_chain.join(" -> "));
+            redirect_chain
+        }
+        _ => vec![],
+    }
+}
+
+/// Returns the cache entry of a previous download of the URL, if its file can be reused.
+/// The file must still exist in the output target and must be hashed with the same algorithm.
+fn cached_download(
+    url: &str,
+    download_properties: &DownloadProperties,
+    default_file_name: Option<&str>,
+) -> Option<CacheEntry> {
+    if !download_properties.use_cache
+        || download_properties.decompress
+        || download_properties.output_mode != OutputMode::File
+    {
+        return None;
+    }
+
+    let cache_entry = download_cache::lookup(url)?;
+
+    let file_size = std::fs::metadata(&cache_entry.file_location)
+        .ok()
+        .filter(|metadata| metadata.is_file())?
+        .len();
+
+    let same_target = cache_entry
+        .file_location
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        == download_properties.output_target.canonicalize().ok();
+
+    let same_name = default_file_name.is_none_or(|file_name| {
+        cache_entry
+            .file_location
+            .file_name()
+            .is_some_and(|cached_name| cached_name == file_name)
+    });
+
+    let same_algorithm = cache_entry.algorithm == download_properties.algorithm.to_string();
+
+    (file_size == cache_entry.size && same_target && same_name && same_algorithm)
+        .then_some(cache_entry)
+}
+
+/// Reuses the file of a previous download, if its hash still matches the verified hash.
+/// Returns `None` if the file was modified since the download.
+fn reuse_cached_download(
+    url: &str,
+    cache_entry: CacheEntry,
+    response: &Response<Body>,
+    download_properties: &DownloadProperties,
+) -> Result<Option<DownloadResult>> {
+    log::info!(
+        "The file was not modified since the last download - Verify the local file: {}",
+        utils::absolute_path_as_string(&cache_entry.file_location)
+    );
 
+    let start = Instant::now();
+    let hash_sum = local::get_hash_for_object(
+        cache_entry.file_location.clone(),
+        download_properties.algorithm,
+        false,
+        download_properties.quiet,
+    )?
+    .hash_sum;
+
+    if !hasher::is_hash_equal(&cache_entry.hash_sum, &hash_sum) {
+        log::warn!("The local file was modified since the last download - Download the file again");
+        download_cache::remove(url);
+        return Ok(None);
+    }
+
+    if let Some(expected_size) = download_properties.expected_size {
+        verify_file_size(&cache_entry.file_location, expected_size)
+            .inspect_err(|_| download_cache::remove(url))?;
+    }
+
+    if !download_properties.quiet {
+        print_message(
+            download_properties,
+            format!(
+                "Not modified since the last download, the transfer is skipped: {}",
+                file_name_of(&cache_entry.file_location)
+            ),
+        );
+    }
+
+    Ok(Some(DownloadResult {
+        hash_sum,
+        source_url: url.to_string(),
+        integrity_checks: vec![],
+        redirect_chain: redirect_chain_of(response),
+        from_cache: true,
+        size: hashed_size(Some(&cache_entry.file_location), 0),
+        elapsed: start.elapsed(),
+        final_url: Some(http_client::redact_url(&response.get_uri().to_string())),
+        response_headers: header_list(response.headers()),
+        file_location: Some(cache_entry.file_location),
+    }))
+}
+
+/// Stores the validators of the response and the hash sum of the downloaded file in the download cache
+fn cache_download(
+    url: &str,
+    response_headers: &HeaderMap,
+    file_location: &Path,
+    hash_sum: &str,
+    download_properties: &DownloadProperties,
+) {
+    let Ok(metadata) = std::fs::metadata(file_location) else {
+        return;
+    };
+
+    if let Some(cache_entry) = CacheEntry::from_headers(
+        response_headers,
+        metadata.len(),
+        download_properties.algorithm.to_string(),
+        hash_sum.to_string(),
+        file_location.to_path_buf(),
+    ) {
+        log::debug!("Cache the download of: {}", http_client::redact_url(url));
+        download_cache::store(url, cache_entry);
+    }
+}
+
+/// Copies the content of a `file:` or `data:` URL into the output target.
+/// The content is processed like the body of a server response, so the hash is calculated while it is written.
+fn download_from_local_url(
+    url: &str,
+    download_properties: &DownloadProperties,
+    default_file_name: Option<String>,
+) -> Result<DownloadResult> {
+    let start = Instant::now();
+    let (reader, size): (Box<dyn Read>, usize) = match Url::parse(url) {
+        Ok(parsed_url) if parsed_url.scheme() == "file" => {
+            let source_path = parsed_url.to_file_path().map_err(|_| {
+                DownloadError::new(format!("The URL '{url}' does not refer to a local file"))
+            })?;
+            let file = File::open(&source_path)
+                .and_then(|file| file.metadata().map(|metadata| (file, metadata)))
+                .map_err(|io_err| {
+                    let download_err = DownloadError::new(format!(
+                        "Failed to open file: {}",
+                        utils::absolute_path_as_string(&source_path)
+                    ));
+                    log::error!("{download_err} - Details: {io_err:?}");
+                    download_err
+                })?;
+
+            match file {
+                (file, metadata) if metadata.is_file() => (
+                    Box::new(BufReader::with_capacity(utils::CAPACITY, file)),
+                    metadata.len() as usize,
+                ),
+                _ => {
+                    return Err(DownloadError::new(format!(
+                        "The URL '{url}' does not refer to a file"
+                    ))
+                    .into());
+                }
+            }
+        }
+        _ => {
+            let data = decode_data_url(url).ok_or_else(|| {
+                let download_err = DownloadError::new(format!("Invalid data URL: '{url}'"));
+                log::error!("{download_err}");
+                download_err
+            })?;
+            let size = data.len();
+            (Box::new(std::io::Cursor::new(data)), size)
+        }
+    };
+
+    let file_size_state = FileSizeState::Known(size);
+
+    check_announced_size(&file_size_state, download_properties.expected_size)?;
+
+    let extract_result = default_file_name.or_else(|| {
+        utils::extract_file_name(url, "", &download_properties.os_type)
+            .filter(|_| url.starts_with("file:"))
+    });
+
+    // the media type of a data URL, e.g. 'text/plain' of 'data:text/plain;base64,...'
+    let content_type = url
+        .strip_prefix("data:")
+        .and_then(|data| data.split([';', ',']).next())
+        .filter(|media_type| !media_type.is_empty());
+
+    let file_path = match download_properties.output_mode {
+        OutputMode::File => Some(build_file_path(
+            url,
+            extract_result,
+            content_type,
+            download_properties,
+        )?),
+        OutputMode::Discard | OutputMode::Stdout => None,
+    };
+
+    // copying a file onto itself would truncate the source before it is read
+    if let Ok(parsed_url) = Url::parse(url)
+        && let Ok(source_path) = parsed_url.to_file_path()
+        && let Some(file_path) = &file_path
+        && let (Ok(source), Ok(target)) = (source_path.canonicalize(), file_path.canonicalize())
+        && source == target
+    {
+        let download_err = DownloadError::new(format!(
+            "The source and the output file are the same: {}",
+            utils::absolute_path_as_string(&source)
+        ));
         log::error!("{download_err}");
-        Err(download_err.into())
+        return Err(download_err.into());
+    }
+
+    // a local source provides no digests
+    let mut digest_verifier = DigestVerifier::from_headers(&HeaderMap::new(), false);
+
+    let (file_location, hash_sum, size) = make_download_req(
+        file_path,
+        reader,
+        file_size_state,
+        download_properties,
+        &mut digest_verifier,
+    )?;
+
+    if let (Some(file_location), Some(expected_size)) =
+        (&file_location, download_properties.expected_size)
+    {
+        verify_file_size(file_location, expected_size)?;
+    }
+
+    let (file_location, hash_sum) = match file_location {
+        Some(file_location) if download_properties.decompress => {
+            let (file_location, hash_sum) =
+                decompress_download(file_location, hash_sum, download_properties)?;
+            (Some(file_location), hash_sum)
+        }
+        file_location => (file_location, hash_sum),
+    };
+    let size = hashed_size(file_location.as_deref(), size);
+
+    Ok(DownloadResult {
+        file_location,
+        hash_sum,
+        source_url: url.to_string(),
+        integrity_checks: digest_verifier.finalize(),
+        redirect_chain: vec![],
+        from_cache: false,
+        size,
+        elapsed: start.elapsed(),
+        final_url: None,
+        response_headers: vec![],
+    })
+}
+
+/// Decodes the content of a `data:` URL (RFC 2397), which is either base64 or percent-encoded
+fn decode_data_url(url: &str) -> Option<Vec<u8>> {
+    let (media_type, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let data = percent_encoding::percent_decode_str(data).collect::<Vec<u8>>();
+
+    if media_type.to_ascii_lowercase().ends_with(";base64") {
+        let data: Vec<u8> = data
+            .into_iter()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        base64::engine::general_purpose::STANDARD.decode(data).ok()
     } else {
-        // IMPORTANT: use the url from the response object, because in case of an redirect the
-        // url can differ from the request url when the http client has follows redirects.
-        let uri = response.get_uri().to_string();
-
-        // get the Content-Disposition header
-        let content_disposition = response
-            .headers()
-            .get(CONTENT_DISPOSITION)
-            .map_or("", |header_value| header_value.to_str().unwrap_or_default());
-
-        let extract_result = match download_properties.default_file_name {
-            Some(default_file_name) => Some(default_file_name),
-            None => {
-                // if the user has not specified a default filename via the --rename option
-                // -> try to extract the filename from the server response
-                utils::extract_file_name(&uri, content_disposition, &download_properties.os_type)
+        Some(data)
+    }
+}
+
+/// Fails early, if the announced size of the file differs from the expected size
+fn check_announced_size(file_size_state: &FileSizeState, expected_size: Option<u64>) -> Result<()> {
+    match (file_size_state, expected_size) {
+        // the server announces another size -> it is not necessary to download the file
+        (FileSizeState::Known(total_size), Some(expected_size))
+            if *total_size as u64 != expected_size =>
+        {
+            let download_err = DownloadError::new(format!(
+                "The server announces a file size of {total_size} bytes, but {expected_size} bytes are expected"
+            ));
+            log::error!("{download_err}");
+            Err(download_err.into())
+        }
+        (FileSizeState::Unknown, _) => {
+            // e.g. HTTP/1.0 responses, whose body ends when the server closes the connection
+            log::warn!(
+                "The server response did not contain any information about the file size - Download until the connection is closed"
+            );
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Builds the path under which the file is saved. If no filename was found, the user has to enter a valid filename.
+/// In non-interactive mode a fallback name is derived from the URL and the `Content-Type` instead.
+fn build_file_path(
+    url: &str,
+    file_name: Option<String>,
+    content_type: Option<&str>,
+    download_properties: &DownloadProperties,
+) -> Result<PathBuf> {
+    // credentials of the URL must not be written to the log file
+    let log_url = http_client::redact_url(url);
+    let file_name = match file_name {
+        Some(file_name) => file_name,
+        None if download_properties.multi_progress.is_some() => {
+            let download_err = DownloadError::new(format!(
+                "Could not determine a filename from server response for '{log_url}'. Please specify a name for this download"
+            ));
+            log::error!("{download_err}");
+            return Err(download_err.into());
+        }
+        None if !download_properties.interactive => {
+            let file_name = filename_handling::fallback_file_name(url, content_type);
+
+            if let Err(filename_err) =
+                filename_handling::validate_filename(&download_properties.os_type, &file_name)
+            {
+                let download_err = DownloadError::new(format!(
+                    "Could not determine a filename from server response for '{log_url}' and the fallback name '{file_name}' is invalid [{filename_err}]. Please specify a name with the option [-r, --rename]"
+                ));
+                log::error!("{download_err}");
+                return Err(download_err.into());
             }
-        };
 
-        // check if a filename was found, if not the user have to enter a valid filename
-        let filename = match extract_result {
-            Some(filename) => filename,
-            None => {
-                println!("Could not determine a filename from server response");
-                println!("Please enter a name for the file to be downloaded");
-                filename_handling::enter_and_verify_file_name(&download_properties.os_type)?
+            log::info!(
+                "Could not determine a filename from server response - Use fallback name: {file_name}"
+            );
+            if !download_properties.quiet {
+                print_message(
+                    download_properties,
+                    format!(
+                        "Could not determine a filename from server response, the file is saved as: {file_name}"
+                    ),
+                );
             }
-        };
+            file_name
+        }
+        None => {
+            print_message(
+                download_properties,
+                "Could not determine a filename from server response\nPlease enter a name for the file to be downloaded".to_string(),
+            );
+            filename_handling::enter_and_verify_file_name(&download_properties.os_type)?
+        }
+    };
 
-        // build the final path under which the file is saved
-        let file_path = download_properties.output_target.join(filename);
+    Ok(download_properties.output_target.join(file_name))
+}
 
-        // capture the server response body and turn it into a Reader
-        let body_reader = response.into_body().into_reader();
+/// Checks that the size of the downloaded file matches the expected size
+fn verify_file_size(file_path: &Path, expected_size: u64) -> Result<()> {
+    let file_size = std::fs::metadata(file_path)
+        .map(|metadata| metadata.len())
+        .map_err(|io_err| {
+            let download_err = DownloadError::new(format!(
+                "Failed to read metadata of file: {}",
+                utils::absolute_path_as_string(file_path)
+            ));
+            log::error!("{download_err} - Details: {io_err:?}");
+            download_err
+        })?;
 
-        // start the download process
-        make_download_req(
-            file_path,
-            body_reader,
-            file_size_state,
-            download_properties.algorithm,
-        )
+    // a file of the wrong size must not be mistaken for the expected file
+    verify_size(file_size, expected_size).inspect_err(|_| remove_invalid_file(file_path))
+}
+
+/// Compares the number of downloaded bytes with the expected size
+fn verify_size(file_size: u64, expected_size: u64) -> Result<()> {
+    if file_size != expected_size {
+        let download_err = DownloadError::new(format!(
+            "The size of the downloaded file ({file_size} bytes) does not match the expected size ({expected_size} bytes)"
+        ));
+        log::error!("{download_err}");
+        return Err(download_err.into());
     }
+
+    Ok(())
 }
 
+/// Fetches a small resource (e.g. a signature or a checksum file) into memory.
+/// The download is canceled if the body exceeds the given limit.
+pub fn fetch_bytes(url: &str, max_size: usize, http_options: &HttpOptions) -> Result<Vec<u8>> {
+    // credentials of the URL must not be written to the log file
+    let log_url = http_client::redact_url(url);
+    log::info!("Fetch resource: {log_url}");
+
+    let response = HttpClient::new(http_options)?
+        .get(url)
+        .map_err(|response_err| {
+            let download_err = DownloadError::new(format!(
+                "Failed to fetch resource '{log_url}' [{response_err}]"
+            ));
+            log::error!("{download_err}");
+            download_err
+        })?;
+
+    let content_coding = compression::content_coding(response.headers());
+    let mut body = response.into_body();
+
+    let content = body
+        .with_config()
+        .limit(max_size as u64)
+        .read_to_vec()
+        .map_err(|body_err| {
+            let download_err = DownloadError::new(format!(
+                "Failed to read resource '{log_url}' from server response [{body_err}]"
+            ));
+            log::error!("{download_err}");
+            download_err
+        })?;
+
+    let Some(coding) = content_coding else {
+        return Ok(content);
+    };
+
+    // the limit also applies to the decoded data
+    let mut decoded = vec![];
+    compression::decode_content(content.as_slice(), &coding)?
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|decode_err| {
+            let download_err = DownloadError::new(format!(
+                "Failed to decode resource '{log_url}' [{decode_err}]"
+            ));
+            log::error!("{download_err}");
+            download_err
+        })?;
+
+    if decoded.len() > max_size {
+        let download_err = DownloadError::new(format!(
+            "The resource '{log_url}' exceeds the max. size of {}",
+            utils::convert_bytes_to_human_readable(max_size)
+        ));
+        log::error!("{download_err}");
+        return Err(download_err.into());
+    }
+
+    Ok(decoded)
+}
+
+/// Writes the response body into the file and returns the file path together with the hash sum
 fn make_download_req(
-    file_path: PathBuf,
-    mut body_reader: impl Read,
+    file_path: Option<PathBuf>,
+    body_reader: impl Read,
     file_size_state: FileSizeState,
-    algorithm: Algorithm,
-) -> Result<DownloadResult> {
-    // Create the file to write in
-    let file = File::create(&file_path).map_err(|io_err| {
-        let msg = format!(
-            "Failed to create file: {}",
-            utils::absolute_path_as_string(&file_path),
-        );
+    download_properties: &DownloadProperties,
+    digest_verifier: &mut DigestVerifier,
+) -> Result<(Option<PathBuf>, String, u64)> {
+    let multi_progress = download_properties.multi_progress.as_ref();
+    let throttle = download_properties.limit_rate.map(Throttle::new);
+    let file_name = file_path.as_deref().map(file_name_of).unwrap_or_default();
 
-        let download_err = DownloadError::new(msg);
-        log::error!("{download_err} - Details: {io_err:?}");
-        download_err
-    })?;
+    // the data is either written into the file, to the standard output or only hashed
+    let (writer, output_target): (Box<dyn Write>, String) = match &file_path {
+        Some(file_path) => {
+            let file = File::create(file_path).map_err(|io_err| {
+                let msg = format!(
+                    "Failed to create file: {}",
+                    utils::absolute_path_as_string(file_path),
+                );
+
+                let download_err = DownloadError::new(msg);
+                log::error!("{download_err} - Details: {io_err:?}");
+                download_err
+            })?;
+            (Box::new(file), utils::absolute_path_as_string(file_path))
+        }
+        None if download_properties.output_mode == OutputMode::Stdout => {
+            (Box::new(std::io::stdout().lock()), "stdout".to_string())
+        }
+        None => (Box::new(std::io::sink()), "none (hash only)".to_string()),
+    };
 
     log::info!(
         "Start download - Total file size: {}",
@@ -211,24 +1317,12 @@ fn make_download_req(
         }
     );
 
-    log::info!(
-        "Output target: {}",
-        utils::absolute_path_as_string(&file_path)
-    );
+    log::info!("Output target: {output_target}");
 
     // Build a Progress-Bar or Spinner
     let progress_bar = match file_size_state {
         FileSizeState::Known(total_size) => {
-            let pb = ProgressBar::new(total_size as u64);
-            pb.set_style(
-                ProgressStyle::with_template(
-                    "[{msg}] [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({eta})",
-                )
-                .unwrap_or(ProgressStyle::default_bar())
-                .progress_chars("#>-"),
-            );
-            pb.set_message("Download in progress");
-            pb
+            build_progress_bar(total_size as u64, &file_name, download_properties)
         }
         _ => {
             let spinner = ProgressBar::new_spinner();
@@ -238,66 +1332,106 @@ fn make_download_req(
                     .template("{spinner:.white} {msg}")
                     .unwrap_or_else(|_| ProgressStyle::default_spinner()),
             );
+            let spinner = attach_progress_bar(download_properties, spinner);
             spinner.enable_steady_tick(Duration::from_millis(100));
+            if !download_properties.quiet {
+                term_output::print_plain_progress(&spinner, &format!("Download {file_name}"));
+            }
             spinner
         }
     };
 
-    let mut writer = BufWriter::with_capacity(utils::CAPACITY, file);
+    // one byte more than announced is read, so that an overlong body is detected
+    let mut body_reader = body_reader.take(match file_size_state {
+        FileSizeState::Known(total_size) => total_size as u64 + 1,
+        _ => u64::MAX,
+    });
+
+    let mut writer = BufWriter::with_capacity(utils::CAPACITY, writer);
     let mut buffer = [0u8; utils::CAPACITY];
+    let chunk_size = throttle
+        .as_ref()
+        .map_or(utils::CAPACITY, Throttle::chunk_size);
     let mut downloaded_bytes: usize = 0;
 
     // get the right hasher for the given algorithm
-    let mut hasher = Hasher::new(algorithm);
+    let mut hasher = Hasher::new(download_properties.algorithm);
 
     // Start measuring time for the download
     let start = Instant::now();
 
     let download_result = loop {
         // Try to read from the response body
-        match body_reader.read(&mut buffer) {
+        match body_reader.read(&mut buffer[..chunk_size]) {
             Ok(bytes_read) => {
                 if bytes_read == 0 {
-                    break Ok(downloaded_bytes);
+                    match file_size_state {
+                        FileSizeState::Known(total_size) if downloaded_bytes < total_size => {
+                            break Err(truncated_error(total_size, downloaded_bytes));
+                        }
+                        _ => break Ok(downloaded_bytes),
+                    }
+                }
+
+                if let FileSizeState::Known(total_size) = file_size_state
+                    && downloaded_bytes + bytes_read > total_size
+                {
+                    break Err(overlong_error(total_size));
                 }
 
                 // Calculate the hash sum of the downloaded bytes chunk by chunk
                 hasher.update(&buffer[..bytes_read]);
+                digest_verifier.update(&buffer[..bytes_read]);
 
                 // Try to write read bytes into the BufWriter
-                writer
-                    .write_all(&buffer[..bytes_read])
-                    .map_err(|write_err| {
-                        let download_err = DownloadError::new(format!(
-                            "Unable to write data from server response into file: {}",
-                            utils::absolute_path_as_string(&file_path),
-                        ));
-                        log::error!("{download_err} - Details: {write_err:?}");
-                        download_err
-                    })?;
+                if let Err(write_err) = writer.write_all(&buffer[..bytes_read]) {
+                    let download_err = DownloadError::new(format!(
+                        "Unable to write data from server response into: {output_target}"
+                    ));
+                    log::error!("{download_err} - Details: {write_err:?}");
+                    break Err(download_err.into());
+                }
 
                 // Capture the successfully downloaded bytes
                 downloaded_bytes += bytes_read;
 
+                if let Some(throttle) = &throttle {
+                    throttle.consume(bytes_read);
+                }
+
                 match file_size_state {
                     FileSizeState::Known(total_size) => {
                         let pb_value = min(downloaded_bytes, total_size);
                         progress_bar.set_position(pb_value as u64);
                     }
                     _ => {
+                        progress_bar.set_position(downloaded_bytes as u64);
                         progress_bar.set_message(format!(
-                            "Download in progress... {}",
-                            utils::convert_bytes_to_human_readable(downloaded_bytes)
+                            "Download in progress... {}{}",
+                            utils::convert_bytes_to_human_readable(downloaded_bytes),
+                            match multi_progress {
+                                Some(_) => format!(" [{file_name}]"),
+                                None => String::new(),
+                            }
                         ));
                     }
                 }
             }
-            Err(body_access_err) => {
-                let download_err =
-                    DownloadError::new("Failed to read data from server response".to_string());
-                log::error!("{download_err} - Details: {body_access_err:?}");
-                break Err(download_err);
-            }
+            Err(body_access_err) => match file_size_state {
+                // the connection was closed before the announced number of bytes were received
+                FileSizeState::Known(total_size)
+                    if body_access_err.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    log::debug!("Details: {body_access_err:?}");
+                    break Err(truncated_error(total_size, downloaded_bytes));
+                }
+                _ => {
+                    let download_err =
+                        DownloadError::new("Failed to read data from server response".to_string());
+                    log::error!("{download_err} - Details: {body_access_err:?}");
+                    break Err(download_err.into());
+                }
+            },
         }
     };
 
@@ -306,7 +1440,25 @@ fn make_download_req(
 
     progress_bar.finish_and_clear();
 
-    let written_bytes = download_result?;
+    let written_bytes = match download_result {
+        Ok(written_bytes) => written_bytes,
+        Err(download_err) => {
+            // the incomplete file must not be mistaken for the downloaded file
+            drop(writer);
+            if let Some(file_path) = &file_path {
+                remove_invalid_file(file_path);
+            }
+            return Err(download_err);
+        }
+    };
+
+    writer.flush().map_err(|write_err| {
+        let download_err = DownloadError::new(format!(
+            "Unable to write data from server response into: {output_target}"
+        ));
+        log::error!("{download_err} - Details: {write_err:?}");
+        download_err
+    })?;
 
     // Generate user information
     log::info!(
@@ -314,18 +1466,442 @@ fn make_download_req(
         utils::convert_bytes_to_human_readable(written_bytes)
     );
 
+    // a saved file is checked by the caller
+    if let (None, Some(expected_size)) = (&file_path, download_properties.expected_size) {
+        verify_size(written_bytes as u64, expected_size)?;
+    }
+
     // calculate the total download time
     let total_duration = end - start;
 
-    println!(
-        "\nDownload done in   : {}",
-        utils::calc_duration(total_duration.as_secs())
+    print_download_duration(download_properties, total_duration, &file_name);
+
+    Ok((
+        file_path,
+        hex::encode(hasher.finalize()),
+        written_bytes as u64,
+    ))
+}
+
+/// Returns the size of the saved file, a decompressed file is larger than the transferred data.
+/// Without a saved file the number of transferred bytes is returned.
+fn hashed_size(file_location: Option<&Path>, transferred_bytes: u64) -> u64 {
+    file_location
+        .and_then(|file_location| std::fs::metadata(file_location).ok())
+        .map_or(transferred_bytes, |metadata| metadata.len())
+}
+
+/// Returns the headers in the order of the response, values which are not valid UTF-8 are replaced
+fn header_list(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Removes a file, whose content is incomplete or does not match the expectations
+pub fn remove_invalid_file(file_path: &Path) {
+    if let Err(io_err) = std::fs::remove_file(file_path) {
+        log::error!(
+            "Failed to remove file: {} - Details: {io_err:?}",
+            utils::absolute_path_as_string(file_path)
+        );
+    }
+}
+
+fn overlong_error(expected: usize) -> anyhow::Error {
+    let size_err = BodySizeError::Overlong { expected };
+    log::error!("{size_err}");
+    size_err.into()
+}
+
+fn truncated_error(expected: usize, received: usize) -> anyhow::Error {
+    let size_err = BodySizeError::Truncated { expected, received };
+    log::error!("{size_err}");
+    size_err.into()
+}
+
+/// Splits the file into byte ranges, if several connections should be used and the server
+/// supports range requests for a file with a known size
+fn plan_segments(
+    file_size_state: &FileSizeState,
+    headers: &HeaderMap,
+    connections: u8,
+) -> Option<Vec<(u64, u64)>> {
+    let FileSizeState::Known(total_size) = *file_size_state else {
+        return None;
+    };
+
+    if connections <= 1 {
+        return None;
+    }
+
+    let accepts_ranges = headers
+        .get(ACCEPT_RANGES)
+        .and_then(|header_value| header_value.to_str().ok())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("bytes"));
+
+    if !accepts_ranges {
+        log::info!("The server does not accept range requests - Download with a single connection");
+        return None;
+    }
+
+    let total_size = total_size as u64;
+    let segment_count = min(
+        u64::from(connections),
+        total_size.div_ceil(MIN_SEGMENT_SIZE),
     );
 
-    Ok(DownloadResult {
-        file_location: file_path,
-        hash_sum: hex::encode(hasher.finalize()),
-    })
+    if segment_count <= 1 {
+        return None;
+    }
+
+    let segment_size = total_size.div_ceil(segment_count);
+
+    Some(
+        (0..segment_count)
+            .map(|idx| idx * segment_size)
+            .take_while(|start| *start < total_size)
+            .map(|start| (start, min(start + segment_size, total_size) - 1))
+            .collect(),
+    )
+}
+
+/// Downloads all segments (inclusive byte ranges) concurrently into a preallocated file and
+/// returns the file path together with the hash sum of the assembled file
+fn make_segmented_download_req(
+    http_client: &HttpClient,
+    uri: &str,
+    file_path: PathBuf,
+    segments: &[(u64, u64)],
+    download_properties: &DownloadProperties,
+    digest_verifier: &mut DigestVerifier,
+) -> Result<(PathBuf, String)> {
+    let multi_progress = download_properties.multi_progress.as_ref();
+    let throttle = download_properties.limit_rate.map(Throttle::new);
+    let throttle = throttle.as_ref();
+
+    // the last byte of the last segment is the last byte of the file
+    let total_size = segments.last().map_or(0, |(_, end)| end + 1);
+
+    // Create the file and reserve the required space, so that each segment can be written at its position
+    File::create(&file_path)
+        .and_then(|file| file.set_len(total_size))
+        .map_err(|io_err| {
+            let msg = format!(
+                "Failed to create file: {}",
+                utils::absolute_path_as_string(&file_path),
+            );
+
+            let download_err = DownloadError::new(msg);
+            log::error!("{download_err} - Details: {io_err:?}");
+            download_err
+        })?;
+
+    log::info!(
+        "Start download with {} connections - Total file size: {}",
+        segments.len(),
+        utils::convert_bytes_to_human_readable(total_size as usize)
+    );
+
+    log::info!(
+        "Output target: {}",
+        utils::absolute_path_as_string(&file_path)
+    );
+
+    let progress_bar =
+        build_progress_bar(total_size, &file_name_of(&file_path), download_properties);
+
+    // Start measuring time for the download
+    let start = Instant::now();
+
+    let download_result: Result<()> = std::thread::scope(|scope| {
+        let handles: Vec<_> = segments
+            .iter()
+            .map(|segment| {
+                let (file_path, progress_bar) = (&file_path, &progress_bar);
+                scope.spawn(move || {
+                    download_segment(
+                        http_client,
+                        uri,
+                        file_path,
+                        *segment,
+                        progress_bar,
+                        throttle,
+                    )
+                })
+            })
+            .collect();
+
+        // join all threads, before the first error is returned
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle.join().unwrap_or_else(|_| {
+                    Err(DownloadError::new("A download segment panicked".to_string()).into())
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .collect()
+    });
+
+    if let Err(download_err) = download_result {
+        progress_bar.finish_and_clear();
+
+        // the preallocated file is incomplete and must not be mistaken for the downloaded file
+        remove_invalid_file(&file_path);
+        return Err(download_err);
+    }
+
+    // Get the time where download is done
+    let total_duration = start.elapsed();
+
+    log::info!(
+        "Download finished - Processed file size: {}",
+        utils::convert_bytes_to_human_readable(total_size as usize)
+    );
+
+    // the segments were written out of order -> hash the assembled file
+    progress_bar.set_position(0);
+    progress_bar.set_message(match multi_progress {
+        Some(_) => format!("Calculate hash sum: {}", file_name_of(&file_path)),
+        None => "Calculate hash sum".to_string(),
+    });
+
+    let hash_result = hash_downloaded_file(
+        &file_path,
+        download_properties.algorithm,
+        digest_verifier,
+        &progress_bar,
+    );
+
+    progress_bar.finish_and_clear();
+
+    let hash_sum = hash_result?;
+
+    print_download_duration(
+        download_properties,
+        total_duration,
+        &file_name_of(&file_path),
+    );
+
+    Ok((file_path, hash_sum))
+}
+
+/// Downloads the given inclusive byte range and writes it at the same position into the file
+fn download_segment(
+    http_client: &HttpClient,
+    uri: &str,
+    file_path: &Path,
+    (start, end): (u64, u64),
+    progress_bar: &ProgressBar,
+    throttle: Option<&Throttle>,
+) -> Result<()> {
+    log::debug!("Start download of segment: bytes {start}-{end}");
+
+    let response = http_client
+        .get_range(uri, (start, end))
+        .map_err(|response_err| {
+            let download_err = DownloadError::new(format!(
+                "Failed to download segment {start}-{end} [{response_err}]"
+            ));
+            log::error!("{download_err}");
+            download_err
+        })?;
+
+    let content_range = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|header_value| header_value.to_str().ok())
+        .and_then(parse_content_range);
+
+    if response.status() != StatusCode::PARTIAL_CONTENT
+        || content_range
+            .is_none_or(|content_range| content_range.start != start || content_range.end != end)
+    {
+        let download_err = DownloadError::new(format!(
+            "The server did not respond with the requested segment {start}-{end} [http status: {}]",
+            response.status()
+        ));
+        log::error!("{download_err}");
+        return Err(download_err.into());
+    }
+
+    let write_err = |io_err: std::io::Error| {
+        let download_err = DownloadError::new(format!(
+            "Unable to write data from server response into file: {}",
+            utils::absolute_path_as_string(file_path),
+        ));
+        log::error!("{download_err} - Details: {io_err:?}");
+        download_err
+    };
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(file_path)
+        .map_err(write_err)?;
+    file.seek(SeekFrom::Start(start)).map_err(write_err)?;
+
+    let mut writer = BufWriter::with_capacity(utils::CAPACITY, file);
+    let segment_size = end - start + 1;
+    // one byte more than requested is read, so that an overlong segment is detected
+    let mut body_reader = response.into_body().into_reader().take(segment_size + 1);
+    let mut buffer = vec![0u8; throttle.map_or(utils::CAPACITY, Throttle::chunk_size)];
+    let mut received_bytes: u64 = 0;
+
+    loop {
+        let bytes_read = body_reader.read(&mut buffer).map_err(|body_access_err| {
+            let download_err =
+                DownloadError::new("Failed to read data from server response".to_string());
+            log::error!("{download_err} - Details: {body_access_err:?}");
+            download_err
+        })?;
+
+        if bytes_read == 0 {
+            break;
+        }
+
+        // the bytes of the next segment must not be overwritten
+        if received_bytes + bytes_read as u64 > segment_size {
+            log::error!("The segment {start}-{end} is overlong");
+            return Err(overlong_error(segment_size as usize));
+        }
+
+        writer.write_all(&buffer[..bytes_read]).map_err(write_err)?;
+        received_bytes += bytes_read as u64;
+        progress_bar.inc(bytes_read as u64);
+
+        if let Some(throttle) = throttle {
+            throttle.consume(bytes_read);
+        }
+    }
+
+    writer.flush().map_err(write_err)?;
+
+    if received_bytes != segment_size {
+        log::error!("The segment {start}-{end} is incomplete");
+        return Err(truncated_error(
+            segment_size as usize,
+            received_bytes as usize,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Calculates the hash sum of the downloaded file and shows the progress in the given progress bar
+fn hash_downloaded_file(
+    file_path: &Path,
+    algorithm: Algorithm,
+    digest_verifier: &mut DigestVerifier,
+    progress_bar: &ProgressBar,
+) -> Result<String> {
+    let read_err = |io_err: std::io::Error| {
+        let msg = format!(
+            "Failed to read from file: {}",
+            utils::absolute_path_as_string(file_path),
+        );
+        log::error!("{msg} - Details: {io_err:?}");
+        anyhow::anyhow!(msg)
+    };
+
+    let mut reader =
+        BufReader::with_capacity(utils::CAPACITY, File::open(file_path).map_err(read_err)?);
+    let mut buffer = vec![0u8; utils::CAPACITY];
+    let mut hasher = Hasher::new(algorithm);
+
+    loop {
+        let bytes_read = reader.read(&mut buffer).map_err(read_err)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+        digest_verifier.update(&buffer[..bytes_read]);
+        progress_bar.inc(bytes_read as u64);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Builds the progress bar for a download with a known file size
+fn build_progress_bar(
+    total_size: u64,
+    file_name: &str,
+    download_properties: &DownloadProperties,
+) -> ProgressBar {
+    let pb = ProgressBar::new(total_size);
+    pb.set_style(
+        ProgressStyle::with_template(
+            "[{msg}] [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+        )
+        .unwrap_or(ProgressStyle::default_bar())
+        .progress_chars("#>-"),
+    );
+    pb.set_message(match download_properties.multi_progress {
+        // several downloads are running at the same time -> show which file is downloaded
+        Some(_) => file_name.to_string(),
+        None => "Download in progress".to_string(),
+    });
+    let pb = attach_progress_bar(download_properties, pb);
+    if !download_properties.quiet {
+        term_output::print_plain_progress(&pb, &format!("Download {file_name}"));
+    }
+    pb
+}
+
+fn print_download_duration(
+    download_properties: &DownloadProperties,
+    total_duration: Duration,
+    file_name: &str,
+) {
+    if download_properties.quiet {
+        return;
+    }
+
+    match &download_properties.multi_progress {
+        Some(multi_progress) => {
+            let _ = multi_progress.println(format!(
+                "Download done in {} : {file_name}",
+                utils::calc_duration(total_duration.as_secs()),
+            ));
+        }
+        // the standard output only contains the downloaded data
+        None if download_properties.output_mode == OutputMode::Stdout => eprintln!(
+            "\nDownload done in   : {}",
+            utils::calc_duration(total_duration.as_secs())
+        ),
+        None => println!(
+            "\nDownload done in   : {}",
+            utils::calc_duration(total_duration.as_secs())
+        ),
+    }
+}
+
+/// Attaches the progress bar to the multi progress display, if several downloads are running.
+/// In quiet mode the progress bar is hidden.
+fn attach_progress_bar(download_properties: &DownloadProperties, pb: ProgressBar) -> ProgressBar {
+    if download_properties.quiet {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+        return pb;
+    }
+
+    match &download_properties.multi_progress {
+        Some(multi_progress) => multi_progress.add(pb),
+        None => pb,
+    }
+}
+
+fn file_name_of(file_path: &Path) -> String {
+    file_path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default()
 }
 
 /// Determine the file size state from the server response
@@ -375,50 +1951,73 @@ fn get_content_length(headers: &HeaderMap) -> FileSizeState {
     })
 }
 
-/// Try to get the `Content-Range` header value
+/// Try to get the `Content-Range` header value.
+///
+/// The size of the response body is determined by the range, e.g. `bytes 0-1023/4096` contains 1024 bytes.
 fn get_content_range(headers: &HeaderMap) -> FileSizeState {
     headers.get(CONTENT_RANGE).map_or(FileSizeState::Unknown, |header_value| {
         match header_value.to_str() {
-            Ok(value) => {
-                // try to extract total size of the file to be downloaded
-                match value.split('/').next_back() {
-                    Some(total_size) => {
-                        // if the last part of the header value contains a '*' the total size is unknown
-                        // for example: Content-Range: bytes 0-1023/*
-                        // so we can use a chunked download
-                        if total_size.contains("*") {
-                            FileSizeState::Chunked
-                        } else {
-                            // try to get the total size as unsigned integer
-                            match total_size.parse::<usize>() {
-                                Ok(total_size) => {
-                                    if total_size > 0 {
-                                        FileSizeState::Known(total_size)
-                                    } else {
-                                        log::error!(
-                                            "The server response contains an invalid value for the file size. It can not be zero - {CONTENT_RANGE}: {total_size}"
-                                        );
-                                        FileSizeState::Unknown
-                                    }
-                                }
-                                Err(parse_err) => {
-                                    log::error!("The server response contains an invalid value for the file size.");
-                                    log::error!(
-                                        "{CONTENT_RANGE}: {total_size} --> {parse_err}"
-                                    );
-                                    FileSizeState::Unknown
-                                }
-                            }
-                        }
+            Ok(value) => match parse_content_range(value) {
+                Some(content_range) => {
+                    // a range which does not cover the whole file, e.g. if the server ignores the missing Range header
+                    if content_range
+                        .total
+                        .is_some_and(|total| content_range.start != 0 || content_range.end + 1 != total)
+                    {
+                        log::warn!(
+                            "The server response only contains a part of the file - {CONTENT_RANGE}: {value}"
+                        );
                     }
-                    None => FileSizeState::Unknown,
+                    FileSizeState::Known(content_range.len() as usize)
                 }
-            }
+                None => {
+                    log::error!(
+                        "The server response contains an invalid value for the file size - {CONTENT_RANGE}: {value}"
+                    );
+                    FileSizeState::Unknown
+                }
+            },
             Err(_) => FileSizeState::Unknown,
         }
     })
 }
 
+/// The inclusive byte range of a `Content-Range` header
+#[derive(Debug, PartialEq, Eq)]
+struct ContentRange {
+    start: u64,
+    end: u64,
+    /// The size of the whole file, unknown if the server sends a `*`
+    total: Option<u64>,
+}
+
+impl ContentRange {
+    fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Parses a `Content-Range` header value like `bytes 0-1023/4096` or `bytes 0-1023/*`.
+/// Unsatisfied ranges (`bytes */4096`) and invalid ranges are rejected.
+fn parse_content_range(value: &str) -> Option<ContentRange> {
+    let (unit, range) = value.trim().split_once(' ')?;
+    if !unit.eq_ignore_ascii_case("bytes") {
+        return None;
+    }
+
+    let (range, total) = range.trim().split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.parse::<u64>().ok()?, end.parse::<u64>().ok()?);
+
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse::<u64>().ok()?),
+    };
+
+    let is_valid = start <= end && total.is_none_or(|total| end < total);
+    is_valid.then_some(ContentRange { start, end, total })
+}
+
 /// Try to get the `Transfer-Encoding` header and the `chunked` value.
 fn get_transfer_encoding(headers: &HeaderMap) -> FileSizeState {
     headers
@@ -448,3 +2047,417 @@ fn get_transfer_encoding(headers: &HeaderMap) -> FileSizeState {
 //         headers: response_headers,
 //     }
 // }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Digest;
+    use std::io::Cursor;
+
+    fn range_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        headers
+    }
+
+    fn test_properties(expected_size: Option<u64>) -> DownloadProperties {
+        DownloadProperties {
+            algorithm: Algorithm::SHA2_256,
+            url: "http://127.0.0.1/file.bin".to_string(),
+            mirrors: vec![],
+            mirror_order: MirrorOrder::InOrder,
+            expected_hash: None,
+            expected_size,
+            output_target: std::env::temp_dir(),
+            output_mode: OutputMode::File,
+            default_file_name: None,
+            interactive: false,
+            os_type: OS::Linux,
+            connections: 1,
+            limit_rate: None,
+            use_cache: false,
+            hash_raw: false,
+            decompress: false,
+            infer_extension: false,
+            strict: false,
+            quiet: true,
+            http_options: HttpOptions {
+                proxy: None,
+                ca_cert: None,
+                client_cert: None,
+                client_key: None,
+                insecure: false,
+                headers: vec![],
+                credentials: None,
+                connect_timeout: Duration::from_secs(1),
+                read_timeout: None,
+                max_time: None,
+                max_redirects: 0,
+                accept_encoding: vec![],
+            },
+            multi_progress: None,
+        }
+    }
+
+    fn test_file_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hashguard_{}_{name}", std::process::id()))
+    }
+
+    /// Yields its data and then fails like a connection, which is closed too early
+    struct ClosedConnection(Cursor<Vec<u8>>);
+
+    impl Read for ClosedConnection {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => Err(std::io::ErrorKind::UnexpectedEof.into()),
+                bytes_read => Ok(bytes_read),
+            }
+        }
+    }
+
+    #[test]
+    fn test_download_truncated_body() {
+        let file_path = test_file_path("truncated.bin");
+        let result = make_download_req(
+            Some(file_path.clone()),
+            Cursor::new(vec![1u8; 100]),
+            FileSizeState::Known(1000),
+            &test_properties(None),
+            &mut DigestVerifier::from_headers(&HeaderMap::new(), false),
+        );
+
+        let size_err = result.unwrap_err().downcast::<BodySizeError>().unwrap();
+        assert_eq!(
+            size_err,
+            BodySizeError::Truncated {
+                expected: 1000,
+                received: 100
+            }
+        );
+        // the incomplete file is removed
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_download_overlong_body() {
+        let file_path = test_file_path("overlong.bin");
+        let result = make_download_req(
+            Some(file_path.clone()),
+            Cursor::new(b"hello world".to_vec()),
+            FileSizeState::Known(5),
+            &test_properties(None),
+            &mut DigestVerifier::from_headers(&HeaderMap::new(), false),
+        );
+
+        let size_err = result.unwrap_err().downcast::<BodySizeError>().unwrap();
+        assert_eq!(size_err, BodySizeError::Overlong { expected: 5 });
+        // the invalid file is removed
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_download_unknown_size() {
+        let body = vec![7u8; 3 * utils::CAPACITY + 5];
+        let (file_location, hash_sum, size) = make_download_req(
+            None,
+            Cursor::new(body.clone()),
+            FileSizeState::Unknown,
+            &DownloadProperties {
+                output_mode: OutputMode::Discard,
+                ..test_properties(None)
+            },
+            &mut DigestVerifier::from_headers(&HeaderMap::new(), false),
+        )
+        .unwrap();
+
+        assert_eq!(file_location, None);
+        assert_eq!(size, body.len() as u64);
+        assert_eq!(hash_sum, hex::encode(sha2::Sha256::digest(&body)));
+
+        // the number of hashed bytes is compared with the expected size
+        let result = make_download_req(
+            None,
+            Cursor::new(body.clone()),
+            FileSizeState::Chunked,
+            &DownloadProperties {
+                output_mode: OutputMode::Discard,
+                ..test_properties(Some(body.len() as u64 - 1))
+            },
+            &mut DigestVerifier::from_headers(&HeaderMap::new(), false),
+        );
+        assert!(result.unwrap_err().is::<DownloadError>());
+    }
+
+    #[test]
+    fn test_verify_file_size() {
+        let file_path = test_file_path("expected_size.bin");
+        std::fs::write(&file_path, b"hello").unwrap();
+
+        assert!(verify_file_size(&file_path, 5).is_ok());
+        assert!(file_path.exists());
+
+        // a file of the wrong size is removed
+        assert!(verify_file_size(&file_path, 6).is_err());
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_read_body_prefix_truncated() {
+        let body_reader = Box::new(ClosedConnection(Cursor::new(vec![1u8; 100])));
+        let size_err = read_body_prefix(body_reader, &FileSizeState::Known(1000))
+            .err()
+            .unwrap()
+            .downcast::<BodySizeError>()
+            .unwrap();
+        assert_eq!(
+            size_err,
+            BodySizeError::Truncated {
+                expected: 1000,
+                received: 100
+            }
+        );
+
+        let (mut body_reader, content_prefix) = read_body_prefix(
+            Box::new(Cursor::new(vec![1u8; 1000])),
+            &FileSizeState::Known(1000),
+        )
+        .unwrap();
+        assert_eq!(content_prefix.len(), CONTENT_PREFIX_LEN);
+        let mut body = vec![];
+        body_reader.read_to_end(&mut body).unwrap();
+        assert_eq!(body.len(), 1000);
+    }
+
+    #[test]
+    fn test_execute_download_mirrors() {
+        let file_name = format!("hashguard_{}_mirror.txt", std::process::id());
+        let properties = |mirrors: Vec<&str>| DownloadProperties {
+            url: "file:///hashguard/missing/file.txt".to_string(),
+            mirrors: mirrors.into_iter().map(String::from).collect(),
+            expected_hash: Some(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string(),
+            ),
+            default_file_name: Some(file_name.clone()),
+            ..test_properties(None)
+        };
+
+        // a failed download and a file with another hash are skipped
+        let download_result = execute_download(properties(vec![
+            "data:,world",
+            "data:,hello",
+            "data:,other",
+        ]))
+        .unwrap();
+        assert_eq!(download_result.source_url, "data:,hello");
+        let file_location = download_result.file_location.unwrap();
+        assert_eq!(file_location, std::env::temp_dir().join(&file_name));
+        assert_eq!(std::fs::read(&file_location).unwrap(), b"hello");
+        std::fs::remove_file(file_location).unwrap();
+
+        // the result of the last URL is returned, even if the hash does not match
+        let download_result = execute_download(properties(vec!["data:,world"])).unwrap();
+        assert_eq!(download_result.source_url, "data:,world");
+        std::fs::remove_file(download_result.file_location.unwrap()).unwrap();
+
+        // the error of the last URL is returned
+        let mut download_properties = properties(vec!["data:,world"]);
+        std::mem::swap(
+            &mut download_properties.url,
+            &mut download_properties.mirrors[0],
+        );
+        assert!(execute_download(download_properties).is_err());
+        // the file with another hash is removed
+        assert!(!std::env::temp_dir().join(&file_name).exists());
+    }
+
+    #[test]
+    fn test_redact_credentials_in_errors() {
+        // nothing listens on the port of a dropped listener
+        let url = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!(
+                "http://alice:secret@{}/file.bin",
+                listener.local_addr().unwrap()
+            )
+        };
+        let properties = test_properties(None);
+
+        let fetch_err = fetch_bytes(&url, 1024, &properties.http_options).unwrap_err();
+        assert!(format!("{fetch_err:#}").contains("alice:***@"));
+
+        let file_name_err = build_file_path(
+            &url,
+            None,
+            None,
+            &DownloadProperties {
+                multi_progress: Some(MultiProgress::with_draw_target(ProgressDrawTarget::hidden())),
+                ..test_properties(None)
+            },
+        )
+        .unwrap_err();
+
+        let download_err = execute_download(DownloadProperties {
+            url: url.clone(),
+            ..test_properties(None)
+        })
+        .unwrap_err();
+
+        for err in [fetch_err, file_name_err, download_err] {
+            assert!(!format!("{err:#} {err:?}").contains("secret"), "{err:#}");
+        }
+    }
+
+    #[test]
+    fn test_sort_by_response_time() {
+        // answers every request after the delay of the path, e.g. '/200' after 200 ms
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut request = vec![0u8; 1024];
+                    let len = stream.read(&mut request).unwrap_or_default();
+                    let request = String::from_utf8_lossy(&request[..len]).to_string();
+                    let delay = request
+                        .split(' ')
+                        .nth(1)
+                        .and_then(|path| path.trim_start_matches('/').parse().ok())
+                        .unwrap_or_default();
+                    std::thread::sleep(Duration::from_millis(delay));
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    );
+                });
+            }
+        });
+
+        // nothing listens on the port of a dropped listener
+        let unreachable = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/0", listener.local_addr().unwrap())
+        };
+        let slow = format!("http://{address}/300");
+        let fast = format!("http://{address}/0");
+
+        let http_client = HttpClient::new(&test_properties(None).http_options).unwrap();
+        let urls = sort_by_response_time(
+            &http_client,
+            vec![unreachable.clone(), slow.clone(), fast.clone()],
+        );
+        assert_eq!(urls, vec![fast, slow, unreachable]);
+    }
+
+    #[test]
+    fn test_plan_segments() {
+        let total_size = 10 * MIN_SEGMENT_SIZE as usize + 5;
+        let segments =
+            plan_segments(&FileSizeState::Known(total_size), &range_headers(), 4).unwrap();
+
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[0].0, 0);
+        assert_eq!(segments.last().unwrap().1, total_size as u64 - 1);
+        // the segments are contiguous
+        for window in segments.windows(2) {
+            assert_eq!(window[0].1 + 1, window[1].0);
+        }
+    }
+
+    #[test]
+    fn test_plan_segments_small_file() {
+        let total_size = MIN_SEGMENT_SIZE as usize * 2;
+        let segments =
+            plan_segments(&FileSizeState::Known(total_size), &range_headers(), 8).unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                (0, MIN_SEGMENT_SIZE - 1),
+                (MIN_SEGMENT_SIZE, 2 * MIN_SEGMENT_SIZE - 1)
+            ]
+        );
+
+        assert_eq!(
+            plan_segments(&FileSizeState::Known(1024), &range_headers(), 8),
+            None
+        );
+    }
+
+    #[test]
+    fn test_plan_segments_not_supported() {
+        let total_size = 10 * MIN_SEGMENT_SIZE as usize;
+
+        assert_eq!(
+            plan_segments(&FileSizeState::Known(total_size), &HeaderMap::new(), 4),
+            None
+        );
+        assert_eq!(
+            plan_segments(&FileSizeState::Known(total_size), &range_headers(), 1),
+            None
+        );
+        assert_eq!(
+            plan_segments(&FileSizeState::Chunked, &range_headers(), 4),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decode_data_url() {
+        assert_eq!(
+            decode_data_url("data:text/plain;base64,SGVs bG8="),
+            Some(b"Hello".to_vec())
+        );
+        assert_eq!(
+            decode_data_url("data:,Hello%20World"),
+            Some(b"Hello World".to_vec())
+        );
+        assert_eq!(decode_data_url("data:;base64,!!!"), None);
+        assert_eq!(decode_data_url("data:no-comma"), None);
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 0-1023/4096"),
+            Some(ContentRange {
+                start: 0,
+                end: 1023,
+                total: Some(4096)
+            })
+        );
+        assert_eq!(
+            parse_content_range("bytes 100-199/*"),
+            Some(ContentRange {
+                start: 100,
+                end: 199,
+                total: None
+            })
+        );
+        assert_eq!(parse_content_range("bytes */4096"), None);
+        assert_eq!(parse_content_range("bytes 10-5/4096"), None);
+        assert_eq!(parse_content_range("bytes 0-4096/4096"), None);
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+        assert_eq!(parse_content_range("4096"), None);
+    }
+
+    #[test]
+    fn test_get_content_range() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 0-4095/4096"));
+        assert!(matches!(
+            get_content_range(&headers),
+            FileSizeState::Known(4096)
+        ));
+
+        // the size of the body is determined by the range, not by the total size
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 1024-2047/*"));
+        assert!(matches!(
+            get_content_range(&headers),
+            FileSizeState::Known(1024)
+        ));
+
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes */4096"));
+        assert!(matches!(
+            get_content_range(&headers),
+            FileSizeState::Unknown
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use ureq::http::{
+    HeaderMap, HeaderValue,
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+
+use crate::{app, http_client, utils};
+
+const CACHE_FILE_NAME: &str = "download-cache.json";
+
+// concurrent downloads of a batch must not overwrite the changes of each other
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Validators and verified hash sum of a previous download
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub size: u64,
+    pub algorithm: String,
+    pub hash_sum: String,
+    pub file_location: PathBuf,
+}
+
+impl CacheEntry {
+    /// Creates an entry from the response headers, returns `None` if the server sends neither an ETag nor a Last-Modified header
+    pub fn from_headers(
+        headers: &HeaderMap,
+        size: u64,
+        algorithm: String,
+        hash_sum: String,
+        file_location: PathBuf,
+    ) -> Option<Self> {
+        let header_value = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let etag = header_value(ETAG);
+        let last_modified = header_value(LAST_MODIFIED);
+
+        if etag.is_none() && last_modified.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            etag,
+            last_modified,
+            size,
+            algorithm,
+            hash_sum,
+            file_location,
+        })
+    }
+
+    /// Returns the headers of a conditional request, the server answers with 304 if the file is unchanged
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if let Some(etag) = self
+            .etag
+            .as_deref()
+            .and_then(|etag| HeaderValue::from_str(etag).ok())
+        {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+
+        if let Some(last_modified) = self
+            .last_modified
+            .as_deref()
+            .and_then(|last_modified| HeaderValue::from_str(last_modified).ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        headers
+    }
+}
+
+/// Returns the cached entry of the given URL
+pub fn lookup(url: &str) -> Option<CacheEntry> {
+    let _guard = CACHE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    load(&cache_file()).remove(&cache_key(url))
+}
+
+/// Stores the entry of the given URL, an existing entry is replaced
+pub fn store(url: &str, entry: CacheEntry) {
+    update(|entries| {
+        entries.insert(cache_key(url), entry);
+    });
+}
+
+/// Removes the entry of the given URL, e.g. if the cached file was modified
+pub fn remove(url: &str) {
+    update(|entries| {
+        entries.remove(&cache_key(url));
+    });
+}
+
+fn update(modify: impl FnOnce(&mut BTreeMap<String, CacheEntry>)) {
+    let _guard = CACHE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let cache_file = cache_file();
+
+    let mut entries = load(&cache_file);
+    modify(&mut entries);
+
+    // the cache only saves time, so a failure is not an error of the download
+    if let Err(save_err) = save(&cache_file, &entries) {
+        log::warn!(
+            "Failed to save download cache: {} - Details: {save_err:?}",
+            utils::absolute_path_as_string(&cache_file)
+        );
+    }
+}
+
+fn load(cache_file: &Path) -> BTreeMap<String, CacheEntry> {
+    match fs::read_to_string(cache_file) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|parse_err| {
+            log::warn!(
+                "Ignore invalid download cache: {} - Details: {parse_err:?}",
+                utils::absolute_path_as_string(cache_file)
+            );
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+fn save(cache_file: &Path, entries: &BTreeMap<String, CacheEntry>) -> anyhow::Result<()> {
+    let content = serde_json::to_string_pretty(entries)?;
+
+    // write into a temporary file first, so that an interrupted write does not destroy the cache
+    let tmp_file = cache_file.with_extension("json.tmp");
+    fs::write(&tmp_file, content)?;
+    fs::rename(&tmp_file, cache_file)?;
+    Ok(())
+}
+
+fn cache_file() -> PathBuf {
+    app::data_dir().join(CACHE_FILE_NAME)
+}
+
+// credentials of the URL must not be written into the cache
+fn cache_key(url: &str) -> String {
+    http_client::redact_url(url)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_conditional_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        let entry = CacheEntry::from_headers(
+            &headers,
+            3,
+            "SHA2-256".to_string(),
+            "00".to_string(),
+            PathBuf::from("file.bin"),
+        )
+        .unwrap();
+
+        let conditions = entry.conditional_headers();
+        assert_eq!(conditions.get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+        assert_eq!(
+            conditions.get(IF_MODIFIED_SINCE).unwrap(),
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+
+        assert!(
+            CacheEntry::from_headers(
+                &HeaderMap::new(),
+                3,
+                "SHA2-256".to_string(),
+                "00".to_string(),
+                PathBuf::from("file.bin"),
+            )
+            .is_none()
+        );
+    }
+}
@@ -1,18 +1,34 @@
 use std::error::Error;
 use std::fmt;
 use std::io::{Write, stdout};
+use std::path::Path;
 
+use icu_normalizer::ComposingNormalizer;
 use regex::Regex;
+use sha2::{Digest, Sha256};
+use url::Url;
 
-use crate::os_specifics;
+use crate::{os_specifics, utils};
 use anyhow::Result;
 
+// Max. length of the part of a fallback file name, which is derived from the URL
+const MAX_FALLBACK_NAME_LEN: usize = 64;
+
+// Max. length of a file name, in bytes on Linux/MacOsX and in UTF-16 code units on Windows
+const MAX_FILE_NAME_LEN: usize = 255;
+
+// Longer suffixes are not treated as extension, when a file name is shortened
+const MAX_EXTENSION_LEN: usize = 16;
+
 #[derive(Debug)]
 pub enum FilenameError {
     InvalidOnWindows(String),
     InvalidOnUnix(String),
     ReservedFilenameOnWindows,
     EndsWithADot,
+    OnlyDots,
+    ControlChars,
+    TooLong,
 }
 
 impl Error for FilenameError {}
@@ -35,6 +51,18 @@ impl fmt::Display for FilenameError {
             FilenameError::EndsWithADot => {
                 write!(f, "File names on Windows must not end with a dot",)
             }
+            FilenameError::OnlyDots => {
+                write!(f, "File names must not consist of dots only")
+            }
+            FilenameError::ControlChars => {
+                write!(f, "File names must not contain control characters")
+            }
+            FilenameError::TooLong => {
+                write!(
+                    f,
+                    "File names must not be longer than {MAX_FILE_NAME_LEN} bytes (UTF-16 code units on Windows)"
+                )
+            }
         }
     }
 }
@@ -85,6 +113,19 @@ pub fn validate_filename(os_type: &os_specifics::OS, filename: &str) -> Result<(
         return Err(anyhow::anyhow!("Filename can not be empty"));
     }
 
+    // e.g. '.' or '..' would refer to a directory
+    if filename.chars().all(|char| char == '.') {
+        return Err(FilenameError::OnlyDots.into());
+    }
+
+    if filename.chars().any(char::is_control) {
+        return Err(FilenameError::ControlChars.into());
+    }
+
+    if file_name_len(os_type, filename) > MAX_FILE_NAME_LEN {
+        return Err(FilenameError::TooLong.into());
+    }
+
     match os_type {
         os_specifics::OS::Linux | os_specifics::OS::MacOs => {
             if !is_filename_valid_on_unix(filename) {
@@ -149,7 +190,213 @@ pub fn enter_and_verify_file_name(os_type: &os_specifics::OS) -> Result<String>
     }
 }
 
+/// Turns a file name of the server response into a file name, which is safe to use on the given OS:
+/// * the name is normalized to Unicode NFC, so that the same name has the same bytes on all systems
+/// * invalid and control characters are replaced with underscores
+/// * leading and trailing white spaces and on Windows trailing dots are removed
+/// * reserved names on Windows are renamed, e.g. `CON.txt` -> `CON_.txt`
+/// * too long names are shortened, the extension is kept
+///
+/// Returns `None` if nothing usable is left, e.g. for `..`
+pub fn sanitize_filename(os_type: &os_specifics::OS, filename: &str) -> Option<String> {
+    let filename = ComposingNormalizer::new_nfc().normalize(filename);
+    let filename = utils::replace_invalid_chars_with_underscore(&filename, os_type)
+        .chars()
+        .map(|char| if char.is_control() { '_' } else { char })
+        .collect::<String>();
+
+    let mut filename = trim_filename(os_type, &filename).to_string();
+
+    if filename.chars().all(|char| char == '.') {
+        return None;
+    }
+
+    if *os_type == os_specifics::OS::Windows && is_reserved_filename_on_windows(&filename) {
+        let idx = filename.find('.').unwrap_or(filename.len());
+        filename.insert(idx, '_');
+    }
+
+    Some(shorten_filename(os_type, &filename))
+}
+
+// Windows removes trailing dots and spaces silently, so the saved file would get another name
+fn trim_filename<'a>(os_type: &os_specifics::OS, filename: &'a str) -> &'a str {
+    match os_type {
+        os_specifics::OS::Windows => filename.trim().trim_end_matches(['.', ' ']),
+        os_specifics::OS::Linux | os_specifics::OS::MacOs => filename.trim(),
+    }
+}
+
+fn file_name_len(os_type: &os_specifics::OS, filename: &str) -> usize {
+    match os_type {
+        os_specifics::OS::Windows => filename.encode_utf16().count(),
+        os_specifics::OS::Linux | os_specifics::OS::MacOs => filename.len(),
+    }
+}
+
+/// Shortens the file name to the max. length of the OS, the extension is kept
+fn shorten_filename(os_type: &os_specifics::OS, filename: &str) -> String {
+    if file_name_len(os_type, filename) <= MAX_FILE_NAME_LEN {
+        return filename.to_string();
+    }
+
+    let (stem, extension) = match filename.rfind('.') {
+        Some(idx) if idx > 0 && filename[idx..].chars().count() <= MAX_EXTENSION_LEN => {
+            filename.split_at(idx)
+        }
+        _ => (filename, ""),
+    };
+
+    let max_stem_len = MAX_FILE_NAME_LEN - file_name_len(os_type, extension);
+    let mut stem_len = 0;
+    let stem = stem
+        .chars()
+        .take_while(|char| {
+            stem_len += file_name_len(os_type, char.encode_utf8(&mut [0; 4]));
+            stem_len <= max_stem_len
+        })
+        .collect::<String>();
+
+    format!("{}{extension}", trim_filename(os_type, &stem))
+}
+
+/// Returns the file extension for the media type of a `Content-Type` header, e.g. `zip` for `application/zip`
+pub fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    let extension = match media_type.as_str() {
+        "application/zip" | "application/x-zip-compressed" => "zip",
+        "application/gzip" | "application/x-gzip" => "gz",
+        "application/x-xz" => "xz",
+        "application/zstd" => "zst",
+        "application/x-bzip2" => "bz2",
+        "application/x-tar" => "tar",
+        "application/x-7z-compressed" => "7z",
+        "application/java-archive" => "jar",
+        "application/vnd.debian.binary-package" => "deb",
+        "application/x-rpm" => "rpm",
+        "application/x-iso9660-image" => "iso",
+        "application/x-msdownload" | "application/vnd.microsoft.portable-executable" => "exe",
+        "application/x-apple-diskimage" => "dmg",
+        "application/pdf" => "pdf",
+        "application/json" => "json",
+        "application/xml" | "text/xml" => "xml",
+        "text/plain" => "txt",
+        "text/html" => "html",
+        "text/csv" => "csv",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "application/octet-stream" => "bin",
+        _ => return None,
+    };
+
+    Some(extension)
+}
+
+/// Returns the file extension for the magic bytes at the start of the content, e.g. `pdf` for `%PDF-`
+pub fn extension_for_magic_bytes(content: &[u8]) -> Option<&'static str> {
+    let extension = match content {
+        [b'P', b'K', 0x03, 0x04, ..] => "zip",
+        [0x1f, 0x8b, ..] => "gz",
+        [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => "xz",
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => "zst",
+        [b'B', b'Z', b'h', ..] => "bz2",
+        [b'7', b'z', 0xbc, 0xaf, 0x27, 0x1c, ..] => "7z",
+        [0xed, 0xab, 0xee, 0xdb, ..] => "rpm",
+        [b'%', b'P', b'D', b'F', b'-', ..] => "pdf",
+        [0x89, b'P', b'N', b'G', ..] => "png",
+        [0xff, 0xd8, 0xff, ..] => "jpg",
+        [b'G', b'I', b'F', b'8', ..] => "gif",
+        [b'M', b'Z', ..] => "exe",
+        _ if content.starts_with(b"!<arch>\ndebian") => "deb",
+        // the magic of a tar archive is located behind the header of the first file
+        _ if content.get(257..262) == Some(b"ustar".as_slice()) => "tar",
+        _ => return None,
+    };
+
+    Some(extension)
+}
+
+/// Appends an extension to a file name without extension. The extension is derived from the `Content-Type`,
+/// or from the magic bytes of the content if the `Content-Type` names no specific type.
+/// Returns the file name unchanged if no extension can be derived.
+pub fn with_inferred_extension(
+    os_type: &os_specifics::OS,
+    file_name: String,
+    content_type: Option<&str>,
+    content_prefix: &[u8],
+) -> String {
+    if Path::new(&file_name).extension().is_some() {
+        return file_name;
+    }
+
+    let extension = content_type
+        .and_then(extension_for_content_type)
+        .filter(|extension| *extension != "bin")
+        .or_else(|| extension_for_magic_bytes(content_prefix));
+
+    match extension {
+        Some(extension) => {
+            log::info!(
+                "File name '{file_name}' has no extension - Append inferred extension: {extension}"
+            );
+            sanitize_filename(os_type, &format!("{file_name}.{extension}")).unwrap_or(file_name)
+        }
+        None => file_name,
+    }
+}
+
+/// Derives a file name from the URL, if the server response contains no file name and the user can not be asked.
+/// The name consists of the host and path of the URL, a short hash of the URL, so that different URLs
+/// with the same host and path get different names, and the extension of the `Content-Type`.
+///
+/// Example: `https://example.com/api/latest?os=linux` -> `example.com_api_latest-5e0c1a2b.bin`
+pub fn fallback_file_name(url: &str, content_type: Option<&str>) -> String {
+    let (host, path) = match Url::parse(url) {
+        // the path of e.g. a data URL contains the data and not a name
+        Ok(parsed_url) if parsed_url.cannot_be_a_base() => (String::new(), String::new()),
+        Ok(parsed_url) => (
+            parsed_url.host_str().unwrap_or_default().to_string(),
+            parsed_url.path().to_string(),
+        ),
+        Err(_) => (String::new(), String::new()),
+    };
+
+    // only characters, which are valid on all operating systems are kept
+    let name = format!("{host}/{path}")
+        .split(|char: char| !(char.is_ascii_alphanumeric() || char == '.' || char == '-'))
+        .map(|part| part.trim_matches('.'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    let name = match name.char_indices().nth(MAX_FALLBACK_NAME_LEN) {
+        Some((idx, _)) => name[..idx].trim_end_matches(['_', '.']).to_string(),
+        None => name,
+    };
+
+    let name = if name.is_empty() {
+        "download".to_string()
+    } else {
+        name
+    };
+
+    let url_hash = hex::encode(Sha256::digest(url.as_bytes()));
+    let extension = content_type
+        .and_then(extension_for_content_type)
+        .unwrap_or("bin");
+
+    format!("{name}-{}.{extension}", &url_hash[..8])
+}
+
 #[cfg(test)]
+#[allow(clippy::needless_borrows_for_generic_args)]
 mod test {
     use super::*;
 
@@ -218,6 +465,126 @@ mod test {
         assert_eq!(result, "Hello world");
     }
 
+    #[test]
+    fn test_fallback_file_name() {
+        let file_name = fallback_file_name(
+            "https://example.com/api/v1/latest/?os=linux",
+            Some("application/zip; charset=binary"),
+        );
+        assert!(file_name.starts_with("example.com_api_v1_latest-"));
+        assert!(file_name.ends_with(".zip"));
+
+        // the hash of the URL distinguishes URLs with the same host and path
+        assert_ne!(
+            fallback_file_name("https://example.com/latest?os=linux", None),
+            fallback_file_name("https://example.com/latest?os=windows", None)
+        );
+
+        let file_name = fallback_file_name("data:,hello", None);
+        assert!(file_name.starts_with("download-"));
+        assert!(file_name.ends_with(".bin"));
+
+        let os_type = os_specifics::OS::Windows;
+        assert!(
+            validate_filename(
+                &os_type,
+                &fallback_file_name("https://example.com/..", None)
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        let linux = os_specifics::OS::Linux;
+        let windows = os_specifics::OS::Windows;
+
+        assert_eq!(sanitize_filename(&linux, ".."), None);
+        assert_eq!(sanitize_filename(&windows, " . . "), None);
+        assert_eq!(
+            sanitize_filename(&linux, " report\n\t2024.pdf "),
+            Some("report__2024.pdf".to_string())
+        );
+        assert_eq!(
+            sanitize_filename(&windows, "setup.exe. . "),
+            Some("setup.exe".to_string())
+        );
+        assert_eq!(
+            sanitize_filename(&windows, "con.tar.gz"),
+            Some("con_.tar.gz".to_string())
+        );
+        assert_eq!(sanitize_filename(&linux, "CON"), Some("CON".to_string()));
+
+        // 'e' followed by a combining acute accent is composed to 'é'
+        assert_eq!(
+            sanitize_filename(&linux, "cafe\u{301}.txt"),
+            Some("caf\u{e9}.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sanitize_long_filename() {
+        let linux = os_specifics::OS::Linux;
+        let windows = os_specifics::OS::Windows;
+
+        // 'ä' has 2 bytes in UTF-8 and 1 code unit in UTF-16
+        let long_name = format!("{}.tar.gz", "ä".repeat(200));
+
+        let file_name = sanitize_filename(&linux, &long_name).unwrap();
+        assert!(file_name.len() <= MAX_FILE_NAME_LEN);
+        assert!(file_name.ends_with("ää.gz"));
+        assert!(validate_filename(&linux, &file_name).is_ok());
+        assert!(validate_filename(&linux, &long_name).is_err());
+
+        assert_eq!(
+            sanitize_filename(&windows, &long_name),
+            Some(long_name.clone())
+        );
+
+        let long_extension = format!("file.{}", "x".repeat(300));
+        let file_name = sanitize_filename(&linux, &long_extension).unwrap();
+        assert_eq!(file_name.len(), MAX_FILE_NAME_LEN);
+        assert!(file_name.starts_with("file.x"));
+    }
+
+    #[test]
+    fn test_with_inferred_extension() {
+        let os_type = os_specifics::OS::Linux;
+        let pdf = b"%PDF-1.7\n";
+
+        assert_eq!(
+            with_inferred_extension(
+                &os_type,
+                "download".to_string(),
+                Some("application/zip"),
+                pdf
+            ),
+            "download.zip"
+        );
+        // 'application/octet-stream' names no specific type -> magic bytes
+        assert_eq!(
+            with_inferred_extension(
+                &os_type,
+                "download".to_string(),
+                Some("application/octet-stream"),
+                pdf
+            ),
+            "download.pdf"
+        );
+        assert_eq!(
+            with_inferred_extension(&os_type, "report.txt".to_string(), None, pdf),
+            "report.txt"
+        );
+        assert_eq!(
+            with_inferred_extension(&os_type, "download".to_string(), None, b"hashguard"),
+            "download"
+        );
+
+        let mut tar_header = vec![0u8; 512];
+        tar_header[257..262].copy_from_slice(b"ustar");
+        assert_eq!(extension_for_magic_bytes(&tar_header), Some("tar"));
+    }
+
     #[test]
     fn test_filenames_unix() {
         let filename1 = "valid_filename";
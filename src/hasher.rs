@@ -220,6 +220,44 @@ pub fn parse_hash(input: &str) -> Result<HashProperty, HashValidationError> {
     })
 }
 
+/// Looks up the hash of the given file name in the content of a checksum file.
+///
+/// The following line formats are supported:
+/// * GNU coreutils (e.g. `sha256sum`): `<hash>  <file name>` or `<hash> *<file name>`
+/// * BSD tagged (e.g. `sha256sum --tag`): `SHA256 (<file name>) = <hash>`
+///
+/// If the line uses the BSD format, the algorithm is taken from the tag.
+pub fn find_hash_in_checksum_file(content: &str, file_name: &str) -> Option<HashProperty> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .find_map(|line| {
+            let (hash, name, algorithm) = match line.split_once(" (") {
+                Some((tag, rest)) if rest.contains(") = ") => {
+                    let (name, hash) = rest.rsplit_once(") = ")?;
+                    (hash, name, <Algorithm as FromStr>::from_str(tag).ok())
+                }
+                _ => {
+                    let (hash, name) = line.split_once(char::is_whitespace)?;
+                    (hash, name.trim_start().trim_start_matches('*'), None)
+                }
+            };
+
+            // entries may contain a relative path, e.g. './file.txt'
+            let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+
+            if name == file_name && hex::decode(hash).is_ok() {
+                Some(HashProperty {
+                    hash: hash.to_string(),
+                    algorithm,
+                })
+            } else {
+                None
+            }
+        })
+}
+
 // Verifies that every character in the string is a valid hexadecimal digit.
 // Valid hexadecimal (hex) digits are characters that represent numbers in base-16 (hexadecimal) notation.
 // In base-16, digits range from 0 to 15, and these are represented as follows:<br>
@@ -388,6 +426,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn checksum_file_gnu_format() {
+        let content = format!(
+            "# comment\n{DATA_SHA2_224}  other.txt\n{DATA_SHA2_256}  hello.txt\n{DATA_SHA2_512} *./dir/data.bin\n"
+        );
+        assert_eq!(
+            find_hash_in_checksum_file(&content, "hello.txt"),
+            Some(HashProperty {
+                hash: DATA_SHA2_256.to_string(),
+                algorithm: None
+            })
+        );
+        assert_eq!(
+            find_hash_in_checksum_file(&content, "data.bin"),
+            Some(HashProperty {
+                hash: DATA_SHA2_512.to_string(),
+                algorithm: None
+            })
+        );
+        assert_eq!(find_hash_in_checksum_file(&content, "missing.txt"), None);
+    }
+
+    #[test]
+    fn checksum_file_bsd_format() {
+        let content = format!(
+            "SHA256 (hello world.txt) = {DATA_SHA2_256}\nSHA3-512 (data.bin) = {DATA_SHA3_512}\n"
+        );
+        assert_eq!(
+            find_hash_in_checksum_file(&content, "hello world.txt"),
+            Some(HashProperty {
+                hash: DATA_SHA2_256.to_string(),
+                algorithm: Some(Algorithm::SHA2_256)
+            })
+        );
+        assert_eq!(
+            find_hash_in_checksum_file(&content, "data.bin"),
+            Some(HashProperty {
+                hash: DATA_SHA3_512.to_string(),
+                algorithm: Some(Algorithm::SHA3_512)
+            })
+        );
+    }
+
     #[test]
     fn hash_equal() {
         assert!(is_hash_equal(DATA_SHA2_224, DATA_SHA2_224))
@@ -0,0 +1,454 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use crc::{CRC_32_ISCSI, Crc};
+use sha2::Digest;
+use ureq::http::header::{CONTENT_ENCODING, HeaderMap, SERVER};
+
+use crate::hasher::{Algorithm, Hasher};
+
+// header names are written as they are shown in the result, the lookup is case-insensitive
+const REPR_DIGEST: &str = "Repr-Digest";
+const CONTENT_DIGEST: &str = "Content-Digest";
+const LEGACY_DIGEST: &str = "Digest";
+const CONTENT_MD5: &str = "Content-MD5";
+const GOOG_HASH: &str = "x-goog-hash";
+const ETAG: &str = "ETag";
+
+static CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// Digest algorithms which are used by servers in integrity headers
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Crc32c,
+}
+
+impl std::fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            DigestAlgorithm::Md5 => write!(f, "MD5"),
+            DigestAlgorithm::Sha1 => write!(f, "SHA-1"),
+            DigestAlgorithm::Sha256 => write!(f, "SHA2-256"),
+            DigestAlgorithm::Sha512 => write!(f, "SHA2-512"),
+            DigestAlgorithm::Crc32c => write!(f, "CRC32C"),
+        }
+    }
+}
+
+impl DigestAlgorithm {
+    /// Maps the algorithm names of RFC 9530, RFC 3230 and `x-goog-hash`
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "md5" => Some(DigestAlgorithm::Md5),
+            "sha" | "sha-1" => Some(DigestAlgorithm::Sha1),
+            "sha-256" => Some(DigestAlgorithm::Sha256),
+            "sha-512" => Some(DigestAlgorithm::Sha512),
+            "crc32c" => Some(DigestAlgorithm::Crc32c),
+            _ => None,
+        }
+    }
+}
+
+/// A digest of the response body advertised by the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerDigest {
+    /// Name of the header, which contains the digest
+    pub header: String,
+    pub algorithm: DigestAlgorithm,
+    pub digest: Vec<u8>,
+}
+
+/// The result of the comparison between a server digest and the downloaded data
+#[derive(Debug, Clone)]
+pub struct IntegrityCheck {
+    pub header: String,
+    pub algorithm: DigestAlgorithm,
+    pub is_valid: bool,
+}
+
+enum DigestHasher {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha2(Box<Hasher>),
+    Crc32c(crc::Digest<'static, u32>),
+}
+
+impl DigestHasher {
+    fn new(algorithm: DigestAlgorithm) -> Self {
+        match algorithm {
+            DigestAlgorithm::Md5 => DigestHasher::Md5(md5::Md5::new()),
+            DigestAlgorithm::Sha1 => DigestHasher::Sha1(sha1::Sha1::new()),
+            DigestAlgorithm::Sha256 => {
+                DigestHasher::Sha2(Box::new(Hasher::new(Algorithm::SHA2_256)))
+            }
+            DigestAlgorithm::Sha512 => {
+                DigestHasher::Sha2(Box::new(Hasher::new(Algorithm::SHA2_512)))
+            }
+            DigestAlgorithm::Crc32c => DigestHasher::Crc32c(CRC32C.digest()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            DigestHasher::Md5(hasher) => hasher.update(data),
+            DigestHasher::Sha1(hasher) => hasher.update(data),
+            DigestHasher::Sha2(hasher) => hasher.update(data),
+            DigestHasher::Crc32c(digest) => digest.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            DigestHasher::Md5(hasher) => hasher.finalize().to_vec(),
+            DigestHasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            DigestHasher::Sha2(hasher) => hasher.finalize(),
+            // x-goog-hash encodes the checksum in big-endian byte order
+            DigestHasher::Crc32c(digest) => digest.finalize().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+/// Calculates all digests advertised by the server while the response body is streamed
+pub struct DigestVerifier {
+    expected: Vec<ServerDigest>,
+    hashers: Vec<(DigestAlgorithm, DigestHasher)>,
+}
+
+impl DigestVerifier {
+    /// Creates a verifier for all supported integrity headers of the server response.
+    /// `raw_body` is set, if the body is verified as transferred, without decoding the Content-Encoding.
+    pub fn from_headers(headers: &HeaderMap, raw_body: bool) -> Self {
+        Self::new(parse_integrity_headers(headers, raw_body))
+    }
+
+    fn new(expected: Vec<ServerDigest>) -> Self {
+        let mut hashers: Vec<(DigestAlgorithm, DigestHasher)> = vec![];
+        for server_digest in &expected {
+            if !hashers
+                .iter()
+                .any(|(algorithm, _)| *algorithm == server_digest.algorithm)
+            {
+                hashers.push((
+                    server_digest.algorithm,
+                    DigestHasher::new(server_digest.algorithm),
+                ));
+            }
+        }
+
+        Self { expected, hashers }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for (_, hasher) in &mut self.hashers {
+            hasher.update(data);
+        }
+    }
+
+    /// Compares the calculated digests with the digests of the server
+    pub fn finalize(self) -> Vec<IntegrityCheck> {
+        let calculated: Vec<(DigestAlgorithm, Vec<u8>)> = self
+            .hashers
+            .into_iter()
+            .map(|(algorithm, hasher)| (algorithm, hasher.finalize()))
+            .collect();
+
+        self.expected
+            .into_iter()
+            .map(|server_digest| {
+                let is_valid = calculated.iter().any(|(algorithm, digest)| {
+                    *algorithm == server_digest.algorithm && *digest == server_digest.digest
+                });
+
+                if is_valid {
+                    log::info!(
+                        "The {} digest of the header '{}' matches",
+                        server_digest.algorithm,
+                        server_digest.header
+                    );
+                } else {
+                    log::warn!(
+                        "The {} digest of the header '{}' does NOT match",
+                        server_digest.algorithm,
+                        server_digest.header
+                    );
+                }
+
+                IntegrityCheck {
+                    header: server_digest.header,
+                    algorithm: server_digest.algorithm,
+                    is_valid,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Collects the digests of all supported integrity headers:
+/// * RFC 9530 `Repr-Digest` and `Content-Digest`, e.g. `sha-256=:<base64>:`
+/// * RFC 3230 `Digest`, e.g. `SHA-256=<base64>`
+/// * `Content-MD5` and `x-goog-hash`, e.g. `crc32c=<base64>,md5=<base64>`
+/// * S3-style ETags, which contain the hex encoded MD5 digest of objects uploaded in a single part
+///
+/// The digests of an encoded response refer to the transferred data, they are only collected for a raw body.
+/// `Repr-Digest` is skipped in that case, because it may refer to the decoded representation.
+pub fn parse_integrity_headers(headers: &HeaderMap, raw_body: bool) -> Vec<ServerDigest> {
+    let content_encoding = header_values(headers, CONTENT_ENCODING.as_str())
+        .into_iter()
+        .find(|value| !value.eq_ignore_ascii_case("identity"));
+
+    if let Some(content_encoding) = &content_encoding {
+        if !raw_body {
+            log::info!(
+                "The server response is encoded ({content_encoding}) - Integrity headers are ignored"
+            );
+            return vec![];
+        }
+        log::info!(
+            "The server response is encoded ({content_encoding}) - The header '{REPR_DIGEST}' is ignored"
+        );
+    }
+
+    let mut digests = vec![];
+
+    let rfc9530_headers = match content_encoding {
+        Some(_) => vec![CONTENT_DIGEST],
+        None => vec![REPR_DIGEST, CONTENT_DIGEST],
+    };
+
+    for header in rfc9530_headers {
+        for value in header_values(headers, header) {
+            digests.extend(parse_digest_list(header, &value, |value| {
+                value
+                    .strip_prefix(':')
+                    .and_then(|value| value.strip_suffix(':'))
+                    .and_then(decode_base64)
+            }));
+        }
+    }
+
+    for header in [LEGACY_DIGEST, GOOG_HASH] {
+        for value in header_values(headers, header) {
+            digests.extend(parse_digest_list(header, &value, decode_base64));
+        }
+    }
+
+    if let Some(digest) = header_values(headers, CONTENT_MD5)
+        .first()
+        .and_then(|value| decode_base64(value))
+    {
+        digests.push(ServerDigest {
+            header: CONTENT_MD5.to_string(),
+            algorithm: DigestAlgorithm::Md5,
+            digest,
+        });
+    }
+
+    if let Some(digest) = parse_s3_etag(headers) {
+        digests.push(digest);
+    }
+
+    digests
+        .iter()
+        .for_each(|digest| log::debug!("Server digest: {} - {}", digest.header, digest.algorithm));
+
+    digests
+}
+
+/// Parses a comma separated list of `<algorithm>=<value>` entries, unsupported algorithms are ignored
+fn parse_digest_list(
+    header: &str,
+    value: &str,
+    decode: impl Fn(&str) -> Option<Vec<u8>>,
+) -> Vec<ServerDigest> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (name, value) = entry.split_once('=')?;
+            let algorithm = DigestAlgorithm::from_name(name)?;
+            // RFC 9530 allows parameters after the value
+            let value = value.split(';').next().unwrap_or_default().trim();
+            let digest = decode(value).or_else(|| {
+                log::warn!("Invalid digest in header '{header}': {entry}");
+                None
+            })?;
+
+            Some(ServerDigest {
+                header: header.to_string(),
+                algorithm,
+                digest,
+            })
+        })
+        .collect()
+}
+
+/// An ETag is only treated as MD5 digest if the server is S3 compatible, the ETag is not weak
+/// and not the ETag of a multipart upload (`"<hex>-<parts>"`)
+fn parse_s3_etag(headers: &HeaderMap) -> Option<ServerDigest> {
+    let is_s3 = headers
+        .keys()
+        .any(|name| name.as_str().starts_with("x-amz-"))
+        || header_values(headers, SERVER.as_str())
+            .iter()
+            .any(|server| server.contains("AmazonS3"));
+
+    if !is_s3 {
+        return None;
+    }
+
+    let etag = header_values(headers, ETAG).into_iter().next()?;
+    let etag = etag.strip_prefix('"')?.strip_suffix('"')?;
+
+    if etag.len() != 32 {
+        return None;
+    }
+
+    Some(ServerDigest {
+        header: ETAG.to_string(),
+        algorithm: DigestAlgorithm::Md5,
+        digest: hex::decode(etag).ok()?,
+    })
+}
+
+fn header_values(headers: &HeaderMap, name: &str) -> Vec<String> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .collect()
+}
+
+fn decode_base64(value: &str) -> Option<Vec<u8>> {
+    STANDARD.decode(value.trim()).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ureq::http::HeaderValue;
+
+    const DATA: &[u8] = b"Hello World";
+    const MD5: &str = "sQqNsWTgdUEFt6mb5y4/5Q==";
+    const SHA256: &str = "pZGm1Av0IEBKARczz7exkNYsZb8LzaMrV7J32a2fFG4=";
+    const SHA512: &str =
+        "LHT9F+2v2A6ER7DUZ0HuJDt+t03SFJoKsbkkb7MDgvJ+hT2FhXGeDmfL2g2qj1FnEGRhXWRa4nrLFb+xRH9Fmw==";
+
+    fn headers(entries: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn verify(headers: &HeaderMap, data: &[u8]) -> Vec<IntegrityCheck> {
+        let mut verifier = DigestVerifier::from_headers(headers, false);
+        verifier.update(data);
+        verifier.finalize()
+    }
+
+    #[test]
+    fn test_repr_digest() {
+        let headers = headers(&[
+            (
+                REPR_DIGEST,
+                format!("sha-256=:{SHA256}:, sha-512=:{SHA512}:"),
+            ),
+            (
+                CONTENT_DIGEST,
+                format!("unixsum=:AAA=:, sha-256=:{SHA256}:"),
+            ),
+        ]);
+
+        let checks = verify(&headers, DATA);
+        assert_eq!(checks.len(), 3);
+        assert!(checks.iter().all(|check| check.is_valid));
+        assert_eq!(checks[1].algorithm, DigestAlgorithm::Sha512);
+        assert_eq!(checks[2].header, CONTENT_DIGEST);
+    }
+
+    #[test]
+    fn test_legacy_digest_and_content_md5() {
+        let headers = headers(&[
+            (
+                LEGACY_DIGEST,
+                format!("MD5={MD5},SHA=Ck1VqNd45QIvq3AZd8XYQLvEhtA="),
+            ),
+            (CONTENT_MD5, MD5.to_string()),
+        ]);
+
+        let checks = verify(&headers, DATA);
+        assert_eq!(checks.len(), 3);
+        assert!(checks.iter().all(|check| check.is_valid));
+
+        let checks = verify(&headers, b"Hello World!");
+        assert!(checks.iter().all(|check| !check.is_valid));
+    }
+
+    #[test]
+    fn test_goog_hash() {
+        let headers = headers(&[
+            (GOOG_HASH, "crc32c=aR2qLw==".to_string()),
+            (GOOG_HASH, format!("md5={MD5}")),
+        ]);
+
+        let checks = verify(&headers, DATA);
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].algorithm, DigestAlgorithm::Crc32c);
+        assert!(checks.iter().all(|check| check.is_valid));
+    }
+
+    #[test]
+    fn test_s3_etag() {
+        let etag = "\"b10a8db164e0754105b7a99be72e3fe5\"".to_string();
+
+        let s3_headers = headers(&[
+            ("etag", etag.clone()),
+            ("x-amz-request-id", "ABC".to_string()),
+        ]);
+        let checks = verify(&s3_headers, DATA);
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].is_valid);
+
+        // ETags of other servers and multipart uploads are no MD5 digests
+        assert!(parse_integrity_headers(&headers(&[("etag", etag)]), false).is_empty());
+        assert!(
+            parse_integrity_headers(
+                &headers(&[
+                    ("etag", "\"b10a8db164e0754105b7a99be72e3fe5-2\"".to_string()),
+                    ("server", "AmazonS3".to_string()),
+                ]),
+                false
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_encoded_response_is_ignored() {
+        let headers = headers(&[
+            (CONTENT_MD5, MD5.to_string()),
+            ("content-encoding", "gzip".to_string()),
+        ]);
+        assert!(parse_integrity_headers(&headers, false).is_empty());
+    }
+
+    #[test]
+    fn test_encoded_response_with_raw_body() {
+        let headers = headers(&[
+            ("repr-digest", format!("sha-256=:{SHA256}:")),
+            ("content-digest", format!("sha-512=:{SHA512}:")),
+            ("digest", format!("SHA-256={SHA256}")),
+            (CONTENT_MD5, MD5.to_string()),
+            ("content-encoding", "gzip".to_string()),
+        ]);
+
+        let headers = parse_integrity_headers(&headers, true)
+            .into_iter()
+            .map(|digest| digest.header)
+            .collect::<Vec<_>>();
+        assert_eq!(headers, vec![CONTENT_DIGEST, LEGACY_DIGEST, CONTENT_MD5]);
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     term_output, utils,
 };
 use anyhow::Result;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use std::{
     fs::File,
     io::{BufReader, Read},
@@ -14,13 +14,23 @@ use std::{
 };
 use walkdir::WalkDir;
 
+/// The hash sum of a file or directory
+#[derive(Debug)]
+pub struct ObjectHash {
+    pub hash_sum: String,
+    /// Number of hashed bytes
+    pub size: u64,
+    /// Number of files in a directory, `None` for a single file
+    pub walked_files: Option<usize>,
+}
+
 struct HashSpinner {
     spinner: ProgressBar,
     processed_bytes: usize,
 }
 
 impl HashSpinner {
-    fn new() -> Self {
+    fn new(quiet: bool) -> Self {
         let spinner = ProgressBar::new_spinner().with_message(HashSpinner::processed_bytes_msg(0));
         spinner.set_style(
             ProgressStyle::default_spinner()
@@ -28,14 +38,19 @@ impl HashSpinner {
                 .template("{spinner:.white} {msg}")
                 .unwrap_or_else(|_| ProgressStyle::default_spinner()),
         );
-        spinner.enable_steady_tick(Duration::from_millis(100));
+        if quiet {
+            spinner.set_draw_target(ProgressDrawTarget::hidden());
+        } else {
+            spinner.enable_steady_tick(Duration::from_millis(100));
+            term_output::print_plain_progress(&spinner, "Hash calculation");
+        }
         HashSpinner {
             spinner,
             processed_bytes: 0,
         }
     }
 
-    fn new_with_msg(msg: &str) -> Self {
+    fn new_with_msg(msg: &str, quiet: bool) -> Self {
         let spinner = ProgressBar::new_spinner().with_message(format!("|{msg}|"));
         spinner.set_style(
             ProgressStyle::default_spinner()
@@ -43,7 +58,11 @@ impl HashSpinner {
                 .template("{spinner:.white} {msg}")
                 .unwrap_or_else(|_| ProgressStyle::default_spinner()),
         );
-        spinner.enable_steady_tick(Duration::from_millis(100));
+        if quiet {
+            spinner.set_draw_target(ProgressDrawTarget::hidden());
+        } else {
+            spinner.enable_steady_tick(Duration::from_millis(100));
+        }
         HashSpinner {
             spinner,
             processed_bytes: 0,
@@ -57,12 +76,13 @@ impl HashSpinner {
         )
     }
 
-    fn finish_and_clear(self) {
+    fn finish_and_clear(&self) {
         self.spinner.finish_and_clear();
     }
 
     fn update(&mut self, bytes: usize) {
         self.processed_bytes += bytes;
+        self.spinner.set_position(self.processed_bytes as u64);
         self.spinner
             .set_message(HashSpinner::processed_bytes_msg(self.processed_bytes));
     }
@@ -92,11 +112,13 @@ pub fn get_buffer_hash(buffer: &[u8], algorithm: Algorithm) -> String {
 ///
 /// This function is designed for multi-threaded environments where lengthy I/O or CPU-bound operations
 /// benefit from non-blocking UI feedback (spinner) and graceful interruption handling.
+/// In quiet mode the spinner is hidden.
 pub fn get_hash_for_object(
     p: PathBuf,
     algorithm: Algorithm,
     include_names: bool,
-) -> Result<String> {
+    quiet: bool,
+) -> Result<ObjectHash> {
     log::info!(
         "Try to calculate {} hash for {}: '{}'",
         algorithm,
@@ -112,9 +134,9 @@ pub fn get_hash_for_object(
         .spawn(move || {
             // Send the hash sum to the main thread
             let result = if p.is_dir() {
-                hash_directory(p, algorithm, include_names)
+                hash_directory(p, algorithm, include_names, quiet)
             } else {
-                hash_file(p, algorithm, include_names)
+                hash_file(p, algorithm, include_names, quiet)
             };
 
             // Send the result back to the main thread
@@ -144,7 +166,12 @@ pub fn get_hash_for_object(
 
 /// Computes a hash for the given file dependent on the used algorithm.
 /// Includes file name (if needed) and the file content.
-fn hash_file<P: AsRef<Path>>(file: P, algorithm: Algorithm, include_names: bool) -> Result<String> {
+fn hash_file<P: AsRef<Path>>(
+    file: P,
+    algorithm: Algorithm,
+    include_names: bool,
+    quiet: bool,
+) -> Result<ObjectHash> {
     let file_path = file.as_ref();
     let file = File::open(file_path).map_err(|io_err| {
         let msg = format!(
@@ -157,7 +184,7 @@ fn hash_file<P: AsRef<Path>>(file: P, algorithm: Algorithm, include_names: bool)
     })?;
     let mut reader = BufReader::with_capacity(utils::CAPACITY, file);
     let mut hasher = Hasher::new(algorithm);
-    let mut spinner = HashSpinner::new();
+    let mut spinner = HashSpinner::new(quiet);
 
     // Add the file name to the hash
     if include_names {
@@ -191,7 +218,11 @@ fn hash_file<P: AsRef<Path>>(file: P, algorithm: Algorithm, include_names: bool)
 
     spinner.finish_and_clear();
     result?;
-    Ok(hex::encode(hasher.finalize()))
+    Ok(ObjectHash {
+        hash_sum: hex::encode(hasher.finalize()),
+        size: spinner.processed_bytes as u64,
+        walked_files: None,
+    })
 }
 
 /// Computes a hash for the given directory dependent on the used algorithm.
@@ -200,9 +231,10 @@ fn hash_directory<P: AsRef<Path>>(
     dir: P,
     algorithm: Algorithm,
     include_names: bool,
-) -> Result<String> {
+    quiet: bool,
+) -> Result<ObjectHash> {
     let root = dir.as_ref();
-    let mut spinner = HashSpinner::new_with_msg("Read directory recursively");
+    let mut spinner = HashSpinner::new_with_msg("Read directory recursively", quiet);
 
     let entries: Vec<_> = WalkDir::new(root)
         .sort_by_key(|e| e.path().to_path_buf()) // Sort entries to ensure deterministic hashing
@@ -222,6 +254,7 @@ fn hash_directory<P: AsRef<Path>>(
     }
 
     let mut result: Result<()> = Result::Ok(());
+    let mut walked_files = 0;
 
     let mut buf = [0u8; utils::CAPACITY];
 
@@ -245,6 +278,7 @@ fn hash_directory<P: AsRef<Path>>(
         }
 
         if path.is_file() {
+            walked_files += 1;
             match File::open(path) {
                 Ok(file) => {
                     let mut reader = BufReader::with_capacity(utils::CAPACITY, file);
@@ -288,5 +322,9 @@ fn hash_directory<P: AsRef<Path>>(
 
     spinner.finish_and_clear();
     result?;
-    Ok(hex::encode(hasher.finalize()))
+    Ok(ObjectHash {
+        hash_sum: hex::encode(hasher.finalize()),
+        size: spinner.processed_bytes as u64,
+        walked_files: Some(walked_files),
+    })
 }
@@ -1,17 +1,29 @@
 mod app;
+mod batch;
 mod cli;
 mod command_handling;
+mod compression;
+mod config;
+mod content_disposition;
+mod content_sniffing;
 mod download;
+mod download_cache;
 mod filename_handling;
 mod hasher;
+mod http_client;
+mod integrity;
 mod local;
+mod metalink;
+mod netrc;
+mod openpgp;
 mod os_specifics;
+mod output_template;
 mod panic_handling;
+mod signature;
 mod term_output;
 mod utils;
 
 use anyhow::Result;
-use clap::Parser;
 use std::io::Write;
 use termcolor::{Color, ColorSpec, WriteColor};
 
@@ -22,21 +34,32 @@ use crate::{
 
 fn main() -> Result<()> {
     // Parse the given CLI-Arguments
-    let args = Cli::parse();
-    let no_color = args.no_color;
+    let args = Cli::parse_with_config();
+    let color = args.color_mode();
+    // the standard output may only contain the downloaded data or the hash
+    let use_stderr = args.writes_data_to_stdout() || args.quiet;
 
     if let Some(os) = os_specifics::get_os() {
         if let Err(e) = run(args, os) {
-            let mut stdout = term_output::get_stdout(no_color);
-            stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
+            let mut output_stream = if use_stderr {
+                term_output::get_stderr(color)
+            } else {
+                term_output::get_stdout(color)
+            };
+            output_stream.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
 
-            writeln!(stdout, "\nAn error occurred while running {}:", APP_NAME)?;
-            term_output::reset_color(&mut stdout)?;
+            writeln!(
+                output_stream,
+                "\nAn error occurred while running {}:",
+                APP_NAME
+            )?;
+            term_output::reset_color(&mut output_stream)?;
 
-            writeln!(stdout, "{e}\n")?;
+            writeln!(output_stream, "{e}\n")?;
+            std::process::exit(1);
         }
     } else {
-        let mut stdout = term_output::get_stdout(no_color);
+        let mut stdout = term_output::get_stdout(color);
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
 
         writeln!(
@@ -0,0 +1,581 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+
+use crate::{
+    download,
+    hasher::{Algorithm, HashProperty, Hasher},
+    http_client::{self, HttpOptions},
+    utils,
+};
+
+// Max. size of a Metalink file that is loaded into memory, piece hashes of large files need some space
+const MAX_METALINK_SIZE: usize = 16 * 1024 * 1024;
+
+/// A single file described by a Metalink (RFC 5854 or Metalink 3.0) document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetalinkFile {
+    pub name: String,
+    pub size: Option<u64>,
+    /// All HTTP(S) URLs of the file, ordered by their priority
+    pub urls: Vec<String>,
+    /// The hash of the strongest supported algorithm
+    pub hash: Option<HashProperty>,
+    pub pieces: Option<Pieces>,
+}
+
+/// Hashes of the consecutive pieces of a file, used for a partial verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pieces {
+    pub algorithm: Algorithm,
+    pub length: u64,
+    pub hashes: Vec<String>,
+}
+
+/// The result of the piece verification
+#[derive(Debug, Clone)]
+pub struct PieceVerification {
+    pub algorithm: Algorithm,
+    pub total: usize,
+    /// Zero based indices of all pieces whose hash does not match
+    pub corrupted: Vec<usize>,
+    /// Number of bytes beyond the last piece, which are not covered by a piece hash
+    pub surplus_bytes: u64,
+}
+
+impl PieceVerification {
+    pub fn is_valid(&self) -> bool {
+        self.corrupted.is_empty() && self.surplus_bytes == 0
+    }
+}
+
+/// Loads a Metalink file either from a URL or from the local file system and returns the described file.
+///
+/// Metalink files which describe several files are not supported.
+pub fn load(source: &str, http_options: &HttpOptions) -> Result<MetalinkFile> {
+    let content = if utils::is_valid_url(source) {
+        download::fetch_bytes(source, MAX_METALINK_SIZE, http_options)?
+    } else {
+        let path = Path::new(source);
+        fs::read(path).with_context(|| {
+            format!(
+                "Failed to read Metalink file: {}",
+                utils::absolute_path_as_string(path)
+            )
+        })?
+    };
+
+    let mut files = parse(&String::from_utf8_lossy(&content))?;
+    // credentials of the URL must not be written to the log file
+    let source = http_client::redact_url(source);
+
+    match files.len() {
+        0 => {
+            let msg = format!("The Metalink file '{source}' does not describe any file");
+            log::error!("{msg}");
+            Err(anyhow::anyhow!(msg))
+        }
+        1 => {
+            let file = files.remove(0);
+            if file.urls.is_empty() {
+                let msg = format!(
+                    "The Metalink file '{source}' contains no HTTP(S) URL for '{}'",
+                    file.name
+                );
+                log::error!("{msg}");
+                return Err(anyhow::anyhow!(msg));
+            }
+            Ok(file)
+        }
+        n => {
+            let names = files
+                .iter()
+                .map(|file| file.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let msg = format!(
+                "The Metalink file '{source}' describes {n} files ({names}), only Metalink files with a single file are supported"
+            );
+            log::error!("{msg}");
+            Err(anyhow::anyhow!(msg))
+        }
+    }
+}
+
+/// Parses all files of a Metalink document.
+///
+/// Both formats are supported, RFC 5854 (`.meta4`) and the older Metalink 3.0 (`.metalink`),
+/// in which hashes are wrapped by a `verification` and URLs by a `resources` element.
+pub fn parse(content: &str) -> Result<Vec<MetalinkFile>> {
+    let document = Document::parse(content).map_err(|xml_err| {
+        log::error!("Failed to parse Metalink file - Details: {xml_err:?}");
+        anyhow::anyhow!("Failed to parse Metalink file: {xml_err}")
+    })?;
+
+    let root = document.root_element();
+    if root.tag_name().name() != "metalink" {
+        return Err(anyhow::anyhow!(
+            "Invalid Metalink file, the root element must be 'metalink'"
+        ));
+    }
+
+    root.descendants()
+        .filter(|node| is_element(node, "file"))
+        .map(parse_file)
+        .collect()
+}
+
+fn parse_file(file: Node) -> Result<MetalinkFile> {
+    let name = file
+        .attribute("name")
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Invalid Metalink file, a file has no name"))?;
+
+    let size = match child_text(file, "size") {
+        Some(size) => Some(
+            size.parse::<u64>()
+                .with_context(|| format!("Invalid size of '{name}' in Metalink file: {size}"))?,
+        ),
+        None => None,
+    };
+
+    // RFC 5854: <file><hash/></file>, Metalink 3.0: <file><verification><hash/></verification></file>
+    let hash = file
+        .descendants()
+        .filter(|node| is_element(node, "hash"))
+        .filter(|node| {
+            node.parent_element()
+                .is_some_and(|parent| !is_element(&parent, "pieces"))
+        })
+        .filter_map(|node| {
+            let algorithm = node.attribute("type").and_then(parse_algorithm)?;
+            let hash = node.text().unwrap_or_default().trim().to_ascii_lowercase();
+            Some(match is_valid_hash(&hash, algorithm) {
+                true => Ok(HashProperty {
+                    hash,
+                    algorithm: Some(algorithm),
+                }),
+                // a malformed hash must not be skipped, the file would be downloaded unverified
+                false => Err(anyhow::anyhow!(
+                    "Invalid Metalink file, the {algorithm} hash of '{name}' is malformed: '{hash}'"
+                )),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .max_by_key(|hash_property| hash_property.algorithm.map(strength));
+
+    let pieces = file
+        .descendants()
+        .filter(|node| is_element(node, "pieces"))
+        .map(|node| parse_pieces(name, node))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .max_by_key(|pieces| strength(pieces.algorithm));
+
+    // RFC 5854: a lower priority is preferred, Metalink 3.0: a higher preference is preferred
+    let mut urls: Vec<(u32, String)> = file
+        .descendants()
+        .filter(|node| is_element(node, "url"))
+        .filter_map(|node| {
+            let url = node.text()?.trim();
+            if !utils::is_valid_url(url) {
+                log::debug!("Skip unsupported URL in Metalink file: {url}");
+                return None;
+            }
+            let priority = match (node.attribute("priority"), node.attribute("preference")) {
+                (Some(priority), _) => priority.parse().unwrap_or(u32::MAX),
+                (None, Some(preference)) => 100u32.saturating_sub(preference.parse().unwrap_or(0)),
+                (None, None) => u32::MAX,
+            };
+            Some((priority, url.to_string()))
+        })
+        .collect();
+    urls.sort_by_key(|(priority, _)| *priority);
+
+    // a file which is only described by unsupported hashes (e.g. MD5 or SHA-1) must not be downloaded unverified
+    let mut unsupported: Vec<String> = file
+        .descendants()
+        .filter(|node| is_element(node, "hash") || is_element(node, "pieces"))
+        .filter_map(|node| node.attribute("type"))
+        .filter(|name| parse_algorithm(name).is_none())
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect();
+    unsupported.sort_unstable();
+    unsupported.dedup();
+
+    if hash.is_none() && pieces.is_none() && !unsupported.is_empty() {
+        anyhow::bail!(
+            "The Metalink file contains only hashes of unsupported algorithms ({}) for '{name}', the file can not be verified",
+            unsupported.join(", ")
+        );
+    }
+
+    if hash.is_none() {
+        log::warn!("The Metalink file contains no supported hash for '{name}'");
+    }
+
+    Ok(MetalinkFile {
+        name: name.to_string(),
+        size,
+        urls: urls.into_iter().map(|(_, url)| url).collect(),
+        hash,
+        pieces,
+    })
+}
+
+/// Returns the piece hashes, `None` if the algorithm is not supported
+fn parse_pieces(name: &str, pieces: Node) -> Result<Option<Pieces>> {
+    let algorithm = pieces.attribute("type").and_then(parse_algorithm);
+    let length = pieces
+        .attribute("length")
+        .and_then(|length| length.parse::<u64>().ok())
+        .filter(|length| *length > 0);
+
+    let (Some(algorithm), Some(length)) = (algorithm, length) else {
+        log::debug!("Skip unsupported piece hashes of '{name}' in Metalink file");
+        return Ok(None);
+    };
+
+    let hashes: Vec<String> = pieces
+        .children()
+        .filter(|node| is_element(node, "hash"))
+        .filter_map(|node| Some(node.text()?.trim().to_ascii_lowercase()))
+        .collect();
+
+    if hashes.is_empty() || !hashes.iter().all(|hash| is_valid_hash(hash, algorithm)) {
+        anyhow::bail!(
+            "Invalid Metalink file, the {algorithm} piece hashes of '{name}' are malformed"
+        );
+    }
+
+    Ok(Some(Pieces {
+        algorithm,
+        length,
+        hashes,
+    }))
+}
+
+fn is_element(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| is_element(child, name))
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+/// Maps the hash names of the IANA registry (e.g. `sha-256`) and of Metalink 3.0 (e.g. `sha256`)
+/// to the supported algorithms, unsupported algorithms like MD5 or SHA-1 are `None`
+fn parse_algorithm(name: &str) -> Option<Algorithm> {
+    let name = name.trim().to_ascii_lowercase();
+    let name = name
+        .strip_prefix("sha-")
+        .map_or(name.clone(), |bits| format!("sha{bits}"));
+    <Algorithm as FromStr>::from_str(&name).ok()
+}
+
+/// Returns `true` if the hash is a hex string of the digest size of the algorithm
+fn is_valid_hash(hash: &str, algorithm: Algorithm) -> bool {
+    let (bits, _) = strength(algorithm);
+    hash.len() == usize::from(bits / 4) && hex::decode(hash).is_ok()
+}
+
+/// The strength of an algorithm is determined by the digest size, SHA-3 is preferred to SHA-2
+fn strength(algorithm: Algorithm) -> (u16, bool) {
+    match algorithm {
+        Algorithm::SHA2_224 => (224, false),
+        Algorithm::SHA2_256 => (256, false),
+        Algorithm::SHA2_384 => (384, false),
+        Algorithm::SHA2_512 => (512, false),
+        Algorithm::SHA3_224 => (224, true),
+        Algorithm::SHA3_256 => (256, true),
+        Algorithm::SHA3_384 => (384, true),
+        Algorithm::SHA3_512 => (512, true),
+    }
+}
+
+/// Hashes the file piece by piece and compares each piece with the hash of the Metalink file
+pub fn verify_pieces(path: &Path, pieces: &Pieces) -> Result<PieceVerification> {
+    let file = File::open(path).with_context(|| {
+        format!(
+            "Failed to open file: {}",
+            utils::absolute_path_as_string(path)
+        )
+    })?;
+
+    // the pieces must cover the whole file
+    let covered_size = pieces.length.saturating_mul(pieces.hashes.len() as u64);
+    let surplus_bytes = file.metadata()?.len().saturating_sub(covered_size);
+
+    let mut reader = BufReader::with_capacity(utils::CAPACITY, file);
+    let mut buffer = vec![0u8; utils::CAPACITY];
+    let mut corrupted = vec![];
+
+    for (idx, expected_hash) in pieces.hashes.iter().enumerate() {
+        let mut piece = (&mut reader).take(pieces.length);
+        let mut hasher = Hasher::new(pieces.algorithm);
+        let mut piece_size = 0;
+
+        loop {
+            let bytes_read = piece.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+            piece_size += bytes_read;
+        }
+
+        if piece_size == 0 || hex::encode(hasher.finalize()) != *expected_hash {
+            corrupted.push(idx);
+        }
+    }
+
+    if !corrupted.is_empty() {
+        log::warn!(
+            "{} of {} piece(s) do not match the piece hashes of the Metalink file",
+            corrupted.len(),
+            pieces.hashes.len()
+        );
+    }
+
+    if surplus_bytes > 0 {
+        log::warn!(
+            "The file is {surplus_bytes} byte(s) longer than the {} piece(s) of the Metalink file",
+            pieces.hashes.len()
+        );
+    }
+
+    Ok(PieceVerification {
+        algorithm: pieces.algorithm,
+        total: pieces.hashes.len(),
+        corrupted,
+        surplus_bytes,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HASH_256: &str = "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e";
+    const HASH_512: &str = "2c74fd17edafd80e8447b0d46741ee243b7eb74dd2149a0ab1b9246fb30382f27e853d8585719e0e67cbda0daa8f51671064615d645ae27acb15bfb1447f459b";
+
+    #[test]
+    fn test_parse_meta4() {
+        let content = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="example.iso">
+    <size>11</size>
+    <hash type="md5">b10a8db164e0754105b7a99be72e3fe5</hash>
+    <hash type="sha-256">{HASH_256}</hash>
+    <hash type="sha-512">{}</hash>
+    <pieces length="4" type="sha-256">
+      <hash>{HASH_256}</hash>
+      <hash>{HASH_256}</hash>
+      <hash>{HASH_256}</hash>
+    </pieces>
+    <url priority="2">https://mirror2.example.com/example.iso</url>
+    <url priority="1">https://mirror1.example.com/example.iso</url>
+    <url priority="3">ftp://ftp.example.com/example.iso</url>
+    <metaurl mediatype="torrent">https://example.com/example.iso.torrent</metaurl>
+  </file>
+</metalink>"#,
+            HASH_512.to_ascii_uppercase()
+        );
+
+        let files = parse(&content).unwrap();
+        assert_eq!(files.len(), 1);
+
+        let file = &files[0];
+        assert_eq!(file.name, "example.iso");
+        assert_eq!(file.size, Some(11));
+        assert_eq!(
+            file.urls,
+            vec![
+                "https://mirror1.example.com/example.iso",
+                "https://mirror2.example.com/example.iso"
+            ]
+        );
+        assert_eq!(
+            file.hash,
+            Some(HashProperty {
+                hash: HASH_512.to_string(),
+                algorithm: Some(Algorithm::SHA2_512)
+            })
+        );
+
+        let pieces = file.pieces.as_ref().unwrap();
+        assert_eq!(pieces.algorithm, Algorithm::SHA2_256);
+        assert_eq!(pieces.length, 4);
+        assert_eq!(pieces.hashes.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_metalink_v3() {
+        let content = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<metalink version="3.0" xmlns="http://www.metalinker.org/">
+  <files>
+    <file name="example.iso">
+      <size>1024</size>
+      <verification>
+        <hash type="sha1">0a4d55a8d778e5022fab701977c5d840bbc486d0</hash>
+        <hash type="sha256">{HASH_256}</hash>
+        <pieces length="262144" type="sha1">
+          <hash piece="0">0a4d55a8d778e5022fab701977c5d840bbc486d0</hash>
+        </pieces>
+      </verification>
+      <resources>
+        <url type="http" preference="10">http://slow.example.com/example.iso</url>
+        <url type="http" preference="100">http://fast.example.com/example.iso</url>
+      </resources>
+    </file>
+  </files>
+</metalink>"#
+        );
+
+        let files = parse(&content).unwrap();
+        let file = &files[0];
+        assert_eq!(file.size, Some(1024));
+        assert_eq!(
+            file.urls,
+            vec![
+                "http://fast.example.com/example.iso",
+                "http://slow.example.com/example.iso"
+            ]
+        );
+        assert_eq!(
+            file.hash.as_ref().unwrap().algorithm,
+            Some(Algorithm::SHA2_256)
+        );
+        // SHA-1 pieces are not supported
+        assert_eq!(file.pieces, None);
+    }
+
+    #[test]
+    fn test_parse_invalid_metalink() {
+        assert!(parse("<html></html>").is_err());
+        assert!(parse("no xml").is_err());
+        assert!(parse(r#"<metalink><file><size>1</size></file></metalink>"#).is_err());
+        assert!(parse(r#"<metalink><file name="a"><size>abc</size></file></metalink>"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_algorithm() {
+        assert_eq!(parse_algorithm("sha-256"), Some(Algorithm::SHA2_256));
+        assert_eq!(parse_algorithm("SHA-512"), Some(Algorithm::SHA2_512));
+        assert_eq!(parse_algorithm("sha384"), Some(Algorithm::SHA2_384));
+        assert_eq!(parse_algorithm("sha3-256"), Some(Algorithm::SHA3_256));
+        assert_eq!(parse_algorithm("sha-1"), None);
+        assert_eq!(parse_algorithm("md5"), None);
+    }
+
+    #[test]
+    fn test_parse_unsupported_hashes() {
+        let content = r#"<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="example.iso">
+    <hash type="md5">b10a8db164e0754105b7a99be72e3fe5</hash>
+    <hash type="sha-1">0a4d55a8d778e5022fab701977c5d840bbc486d0</hash>
+    <pieces length="4" type="sha-1">
+      <hash>0a4d55a8d778e5022fab701977c5d840bbc486d0</hash>
+    </pieces>
+    <url>https://example.com/example.iso</url>
+  </file>
+</metalink>"#;
+
+        // the file would be downloaded unverified
+        let parse_err = parse(content).unwrap_err();
+        assert!(format!("{parse_err:#}").contains("unsupported algorithms (md5, sha-1)"));
+
+        // a file without any hash can be downloaded
+        let content = r#"<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="example.iso"><url>https://example.com/example.iso</url></file>
+</metalink>"#;
+        assert_eq!(parse(content).unwrap()[0].hash, None);
+    }
+
+    #[test]
+    fn test_verify_pieces() {
+        let path = std::env::temp_dir().join("hashguard_metalink_pieces.bin");
+        fs::write(&path, b"Hello World").unwrap();
+
+        let piece_hash = |data: &[u8]| Hasher::new(Algorithm::SHA2_256).digest_hex_lower(data);
+        let pieces = Pieces {
+            algorithm: Algorithm::SHA2_256,
+            length: 4,
+            hashes: vec![piece_hash(b"Hell"), piece_hash(b"XXXX"), piece_hash(b"rld")],
+        };
+
+        let verification = verify_pieces(&path, &pieces).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(verification.total, 3);
+        assert_eq!(verification.corrupted, vec![1]);
+        assert_eq!(verification.surplus_bytes, 0);
+        assert!(!verification.is_valid());
+    }
+
+    #[test]
+    fn test_verify_pieces_surplus() {
+        let path = std::env::temp_dir().join("hashguard_metalink_surplus.bin");
+        fs::write(&path, b"Hello World").unwrap();
+
+        // the bytes 'o World' are not covered by a piece
+        let pieces = Pieces {
+            algorithm: Algorithm::SHA2_256,
+            length: 4,
+            hashes: vec![Hasher::new(Algorithm::SHA2_256).digest_hex_lower(b"Hell")],
+        };
+
+        let verification = verify_pieces(&path, &pieces).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(verification.corrupted.is_empty());
+        assert_eq!(verification.surplus_bytes, 7);
+        assert!(!verification.is_valid());
+    }
+
+    #[test]
+    fn test_parse_malformed_hashes() {
+        let metalink = |hashes: &str| {
+            format!(
+                r#"<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="example.iso">
+    {hashes}
+    <url>https://example.com/example.iso</url>
+  </file>
+</metalink>"#
+            )
+        };
+
+        let parse_err = parse(&metalink(r#"<hash type="sha-256">zz</hash>"#)).unwrap_err();
+        assert!(
+            parse_err
+                .to_string()
+                .contains("SHA2-256 hash of 'example.iso' is malformed")
+        );
+
+        // a hash of the wrong length is malformed too, even if another hash is valid
+        let content = metalink(&format!(
+            r#"<hash type="sha-256">{HASH_256}</hash><hash type="sha-512">{HASH_256}</hash>"#
+        ));
+        assert!(parse(&content).is_err());
+
+        let content = metalink(&format!(
+            r#"<pieces length="4" type="sha-256"><hash>{HASH_256}</hash><hash>zz</hash></pieces>"#
+        ));
+        assert!(parse(&content).is_err());
+    }
+}
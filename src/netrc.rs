@@ -0,0 +1,162 @@
+use std::{fmt, fs, path::PathBuf};
+
+use crate::utils;
+
+/// Login of a machine in a `.netrc` file
+#[derive(Clone, PartialEq)]
+pub struct NetrcLogin {
+    pub login: String,
+    pub password: String,
+}
+
+impl fmt::Debug for NetrcLogin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NetrcLogin")
+            .field("login", &self.login)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// The content of a `.netrc` file
+#[derive(Debug, Clone, Default)]
+pub struct Netrc {
+    machines: Vec<(String, NetrcLogin)>,
+    default: Option<NetrcLogin>,
+}
+
+impl Netrc {
+    /// Returns the login for the given host, or the `default` login if the host is not listed
+    pub fn find(&self, host: &str) -> Option<&NetrcLogin> {
+        self.machines
+            .iter()
+            .find(|(machine, _)| machine.eq_ignore_ascii_case(host))
+            .map(|(_, login)| login)
+            .or(self.default.as_ref())
+    }
+}
+
+/// Loads the `.netrc` file from the path of the environment variable `NETRC` or from the home directory.
+/// Returns `None` if no file exists.
+pub fn load() -> Option<Netrc> {
+    let path = match std::env::var_os("NETRC") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let home_dir = dirs::home_dir()?;
+            // on Windows the file is traditionally named '_netrc'
+            [".netrc", "_netrc"]
+                .iter()
+                .map(|file_name| home_dir.join(file_name))
+                .find(|path| path.is_file())?
+        }
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => {
+            log::info!("Use netrc file: {}", utils::absolute_path_as_string(&path));
+            Some(parse(&content))
+        }
+        Err(io_err) => {
+            log::warn!(
+                "Failed to read netrc file: {} - Details: {io_err:?}",
+                utils::absolute_path_as_string(&path)
+            );
+            None
+        }
+    }
+}
+
+/// Parses the content of a `.netrc` file, entries without a login or password are ignored
+pub fn parse(content: &str) -> Netrc {
+    let mut netrc = Netrc::default();
+    let mut lines = content.lines();
+
+    // the machine of the current entry, 'None' stands for the default entry
+    let mut machine: Option<Option<String>> = None;
+    let mut login = None;
+    let mut password = None;
+
+    let mut finish_entry =
+        |machine: Option<Option<String>>, login: Option<String>, password: Option<String>| {
+            if let (Some(machine), Some(login), Some(password)) = (machine, login, password) {
+                let netrc_login = NetrcLogin { login, password };
+                match machine {
+                    Some(machine) => netrc.machines.push((machine, netrc_login)),
+                    None => netrc.default = netrc.default.take().or(Some(netrc_login)),
+                }
+            }
+        };
+
+    while let Some(line) = lines.next() {
+        // comments are only allowed at the start of a line
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+
+        while let Some(token) = tokens.next() {
+            match token {
+                "machine" | "default" => {
+                    finish_entry(machine.take(), login.take(), password.take());
+                    machine = Some(match token {
+                        "machine" => tokens.next().map(str::to_string),
+                        _ => None,
+                    });
+                }
+                "login" => login = tokens.next().map(str::to_string),
+                "password" => password = tokens.next().map(str::to_string),
+                "account" => {
+                    tokens.next();
+                }
+                // a macro definition ends with an empty line
+                "macdef" => {
+                    for line in lines.by_ref() {
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                    }
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    finish_entry(machine, login, password);
+
+    netrc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let netrc = parse(
+            "# artifact servers\n\
+             machine artifacts.example.com login alice password s3cret\n\
+             machine other.example.com\n  login bob\n  password hunter2\n\
+             macdef init\ncd /pub\n\n\
+             default login anonymous password guest@",
+        );
+
+        let login = netrc.find("ARTIFACTS.example.com").unwrap();
+        assert_eq!(login.login, "alice");
+        assert_eq!(login.password, "s3cret");
+
+        assert_eq!(netrc.find("other.example.com").unwrap().login, "bob");
+        assert_eq!(
+            netrc.find("unknown.example.com").unwrap().login,
+            "anonymous"
+        );
+        assert!(!format!("{login:?}").contains("s3cret"));
+    }
+
+    #[test]
+    fn test_parse_incomplete_entry() {
+        let netrc = parse("machine example.com login alice");
+        assert!(netrc.find("example.com").is_none());
+    }
+}
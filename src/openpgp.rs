@@ -0,0 +1,1288 @@
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use crc::{CRC_24_OPENPGP, Crc};
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use sha2::{Digest, digest::DynDigest};
+use signature::{Verifier, hazmat::PrehashVerifier};
+
+// packet tags (RFC 9580, section 5)
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_USER_ATTRIBUTE: u8 = 17;
+
+// signature types
+const SIG_BINARY: u8 = 0x00;
+const SIG_TEXT: u8 = 0x01;
+const SIG_CERTIFICATIONS: std::ops::RangeInclusive<u8> = 0x10..=0x13;
+const SIG_SUBKEY_BINDING: u8 = 0x18;
+const SIG_PRIMARY_KEY_BINDING: u8 = 0x19;
+const SIG_DIRECT_KEY: u8 = 0x1f;
+const SIG_KEY_REVOCATION: u8 = 0x20;
+const SIG_SUBKEY_REVOCATION: u8 = 0x28;
+
+// signature subpackets
+const SUBPACKET_CREATION_TIME: u8 = 2;
+const SUBPACKET_EXPIRATION_TIME: u8 = 3;
+const SUBPACKET_KEY_EXPIRATION_TIME: u8 = 9;
+const SUBPACKET_ISSUER: u8 = 16;
+const SUBPACKET_KEY_FLAGS: u8 = 27;
+const SUBPACKET_EMBEDDED_SIGNATURE: u8 = 32;
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+// public key algorithms
+const ALGO_RSA: u8 = 1;
+const ALGO_RSA_SIGN: u8 = 3;
+const ALGO_ECDSA: u8 = 19;
+const ALGO_EDDSA_LEGACY: u8 = 22;
+const ALGO_ED25519: u8 = 27;
+
+// curve OIDs
+const OID_P256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const OID_P384: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+const OID_P521: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x23];
+const OID_ED25519: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01];
+
+// Max. size of an RSA key, larger keys are rejected
+const MAX_RSA_KEY_BITS: usize = 16384;
+
+/// A trusted OpenPGP certificate: the primary key, the first user ID and the signing subkeys.
+/// Revoked certificates and subkeys are not included.
+#[derive(Clone)]
+pub struct Certificate {
+    primary: PublicKey,
+    user_id: Option<String>,
+    /// only subkeys, which are bound to the primary key by a valid signature
+    subkeys: Vec<PublicKey>,
+}
+
+impl Certificate {
+    /// Checks the times of a signature made by a key of the certificate: the signature must be
+    /// created after the key, before the key or the primary key expired and it must not be expired now.
+    pub fn check_validity(&self, key: &PublicKey, signature: &Signature, now: u64) -> Result<()> {
+        let creation_time = signature
+            .creation_time
+            .ok_or_else(|| anyhow::anyhow!("the signature has no creation time"))?;
+
+        if u64::from(creation_time) > now {
+            return Err(anyhow::anyhow!("the signature was created in the future"));
+        }
+        if creation_time < key.creation_time {
+            return Err(anyhow::anyhow!("the signature is older than the key"));
+        }
+        for key in [&self.primary, key] {
+            if key
+                .expiration_time()
+                .is_some_and(|expiration_time| expiration_time <= u64::from(creation_time))
+            {
+                return Err(anyhow::anyhow!(
+                    "the key {} was expired when the signature was created",
+                    hex::encode_upper(key.fingerprint)
+                ));
+            }
+        }
+        if let Some(expiration) = signature.expiration
+            && u64::from(creation_time) + u64::from(expiration) <= now
+        {
+            return Err(anyhow::anyhow!("the signature is expired"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the primary key and all signing subkeys
+    pub fn keys(&self) -> impl Iterator<Item = &PublicKey> {
+        std::iter::once(&self.primary).chain(self.subkeys.iter())
+    }
+
+    /// Returns the fingerprint of the signing key together with the user ID of the certificate
+    pub fn signer(&self, key: &PublicKey) -> String {
+        let fingerprint = hex::encode_upper(key.fingerprint);
+        match &self.user_id {
+            Some(user_id) => format!("{fingerprint} ({user_id})"),
+            None => fingerprint,
+        }
+    }
+}
+
+/// A version 4 public key or subkey
+#[derive(Clone)]
+pub struct PublicKey {
+    fingerprint: [u8; 20],
+    creation_time: u32,
+    /// seconds after the creation, taken from the newest self-signature or binding signature
+    expiration: Option<u32>,
+    /// the packet body, which is hashed by key binding signatures
+    body: Vec<u8>,
+    material: KeyMaterial,
+}
+
+#[derive(Clone)]
+enum KeyMaterial {
+    Rsa(RsaPublicKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
+    EcdsaP256(p256::ecdsa::VerifyingKey),
+    EcdsaP384(p384::ecdsa::VerifyingKey),
+    EcdsaP521(p521::ecdsa::VerifyingKey),
+    /// the algorithm ID of a key, which can not be used for verification (e.g. DSA)
+    Unsupported(u8),
+}
+
+impl PublicKey {
+    /// The key ID consists of the last 8 bytes of the fingerprint
+    fn key_id(&self) -> &[u8] {
+        &self.fingerprint[12..]
+    }
+
+    /// The point in time (seconds since the Unix epoch) when the key expires, `None` if it never expires
+    fn expiration_time(&self) -> Option<u64> {
+        self.expiration
+            .filter(|expiration| *expiration != 0)
+            .map(|expiration| u64::from(self.creation_time) + u64::from(expiration))
+    }
+
+    /// The key is hashed with a prefix by signatures over the key
+    fn hashed_key(&self) -> Vec<u8> {
+        [&key_hash_prefix(&self.body)[..], &self.body].concat()
+    }
+
+    /// Verifies the signature over the given hash digest
+    pub fn verify(&self, signature: &Signature, digest: &[u8]) -> Result<()> {
+        // the signature contains the first two bytes of the digest for a quick check
+        if digest.get(..2) != Some(signature.left16.as_slice()) {
+            return Err(anyhow::anyhow!(
+                "the digest of the signed data does not match"
+            ));
+        }
+
+        let value = |idx: usize, len: usize| {
+            signature
+                .values
+                .get(idx)
+                .and_then(|value| left_pad(value, len))
+                .ok_or_else(|| anyhow::anyhow!("invalid signature value"))
+        };
+        let ecdsa_value =
+            |len: usize| -> Result<Vec<u8>> { Ok([value(0, len)?, value(1, len)?].concat()) };
+
+        match (&self.material, signature.pk_algo) {
+            (KeyMaterial::Rsa(key), ALGO_RSA | ALGO_RSA_SIGN) => {
+                let scheme = match signature.hash_algo {
+                    HashAlgorithm::Sha1 => Pkcs1v15Sign::new::<sha1::Sha1>(),
+                    HashAlgorithm::Sha224 => Pkcs1v15Sign::new::<sha2::Sha224>(),
+                    HashAlgorithm::Sha256 => Pkcs1v15Sign::new::<sha2::Sha256>(),
+                    HashAlgorithm::Sha384 => Pkcs1v15Sign::new::<sha2::Sha384>(),
+                    HashAlgorithm::Sha512 => Pkcs1v15Sign::new::<sha2::Sha512>(),
+                };
+                key.verify(
+                    scheme,
+                    digest,
+                    &value(0, rsa::traits::PublicKeyParts::size(key))?,
+                )?;
+            }
+            (KeyMaterial::Ed25519(key), ALGO_EDDSA_LEGACY | ALGO_ED25519) => {
+                let signature_bytes = match signature.pk_algo {
+                    ALGO_EDDSA_LEGACY => ecdsa_value(32)?,
+                    _ => value(0, 64)?,
+                };
+                let ed25519_signature = ed25519_dalek::Signature::from_slice(&signature_bytes)?;
+                key.verify(digest, &ed25519_signature)?;
+            }
+            (KeyMaterial::EcdsaP256(key), ALGO_ECDSA) => {
+                let ecdsa_signature = p256::ecdsa::Signature::from_slice(&ecdsa_value(32)?)?;
+                key.verify_prehash(digest, &ecdsa_signature)?;
+            }
+            (KeyMaterial::EcdsaP384(key), ALGO_ECDSA) => {
+                let ecdsa_signature = p384::ecdsa::Signature::from_slice(&ecdsa_value(48)?)?;
+                key.verify_prehash(digest, &ecdsa_signature)?;
+            }
+            (KeyMaterial::EcdsaP521(key), ALGO_ECDSA) => {
+                let ecdsa_signature = p521::ecdsa::Signature::from_slice(&ecdsa_value(66)?)?;
+                key.verify_prehash(digest, &ecdsa_signature)?;
+            }
+            (KeyMaterial::Unsupported(algorithm), _) => {
+                return Err(anyhow::anyhow!(
+                    "unsupported public key algorithm: {algorithm}"
+                ));
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "the algorithm of the signature does not match the key"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a signature of this key over keys and user IDs, e.g. a self-signature, a binding or a revocation
+    fn verify_key_signature(&self, signature: &Signature, hashed_data: &[&[u8]]) -> bool {
+        if !signature.is_issued_by(self) {
+            return false;
+        }
+
+        let mut hasher = SignatureHasher::new(signature.hash_algo, false);
+        hashed_data.iter().for_each(|data| hasher.update(data));
+        let digest = hasher.finalize(signature);
+
+        self.verify(signature, &digest)
+            .inspect_err(|err| {
+                log::debug!(
+                    "Invalid key signature (type 0x{:02x}) of key {} - Details: {err}",
+                    signature.sig_type,
+                    hex::encode_upper(self.fingerprint)
+                )
+            })
+            .is_ok()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum HashAlgorithm {
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            2 => Some(HashAlgorithm::Sha1),
+            8 => Some(HashAlgorithm::Sha256),
+            9 => Some(HashAlgorithm::Sha384),
+            10 => Some(HashAlgorithm::Sha512),
+            11 => Some(HashAlgorithm::Sha224),
+            _ => None,
+        }
+    }
+}
+
+/// A version 4 signature
+#[derive(Debug, Clone)]
+pub struct Signature {
+    sig_type: u8,
+    pk_algo: u8,
+    hash_algo: HashAlgorithm,
+    /// the version, the type, the algorithms and the hashed subpackets, which are appended to the signed data
+    hashed_part: Vec<u8>,
+    left16: [u8; 2],
+    issuer_key_id: Option<Vec<u8>>,
+    issuer_fingerprint: Option<Vec<u8>>,
+    key_flags: Option<u8>,
+    /// seconds since the Unix epoch
+    creation_time: Option<u32>,
+    /// seconds after the creation of the signature
+    expiration: Option<u32>,
+    /// seconds after the creation of the key, set by a self-signature or a binding signature
+    key_expiration: Option<u32>,
+    /// the back-signature of a signing subkey, which is embedded into the binding signature
+    embedded_signature: Option<Box<Signature>>,
+    /// the MPIs of the signature, or the native signature of an Ed25519 key
+    values: Vec<Vec<u8>>,
+}
+
+impl Signature {
+    /// Checks whether the signature names the key as issuer.
+    /// A signature without issuer could have been made by any key.
+    pub fn is_issued_by(&self, key: &PublicKey) -> bool {
+        match (&self.issuer_fingerprint, &self.issuer_key_id) {
+            (Some(fingerprint), _) => fingerprint.as_slice() == key.fingerprint,
+            (None, Some(key_id)) => key_id.as_slice() == key.key_id(),
+            (None, None) => true,
+        }
+    }
+
+    /// Returns a hasher for the signed data, the data of a text signature is hashed with CRLF line endings
+    pub fn hasher(&self) -> SignatureHasher {
+        SignatureHasher::new(self.hash_algo, self.sig_type == SIG_TEXT)
+    }
+
+    /// A subkey binding without key flags is accepted, otherwise the key must be allowed to sign data
+    fn allows_signing(&self) -> bool {
+        self.key_flags.is_none_or(|key_flags| key_flags & 0x02 != 0)
+    }
+}
+
+/// Hashes the signed data and the trailer of a signature
+pub struct SignatureHasher {
+    digest: Box<dyn DynDigest>,
+    text_mode: bool,
+    last_was_cr: bool,
+}
+
+impl SignatureHasher {
+    fn new(hash_algo: HashAlgorithm, text_mode: bool) -> Self {
+        let digest: Box<dyn DynDigest> = match hash_algo {
+            HashAlgorithm::Sha1 => Box::new(sha1::Sha1::new()),
+            HashAlgorithm::Sha224 => Box::new(sha2::Sha224::new()),
+            HashAlgorithm::Sha256 => Box::new(sha2::Sha256::new()),
+            HashAlgorithm::Sha384 => Box::new(sha2::Sha384::new()),
+            HashAlgorithm::Sha512 => Box::new(sha2::Sha512::new()),
+        };
+
+        Self {
+            digest,
+            text_mode,
+            last_was_cr: false,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        if !self.text_mode {
+            self.digest.update(data);
+            return;
+        }
+
+        let mut start = 0;
+        for (idx, byte) in data.iter().enumerate() {
+            let previous_is_cr = match idx {
+                0 => self.last_was_cr,
+                _ => data[idx - 1] == b'\r',
+            };
+            if *byte == b'\n' && !previous_is_cr {
+                self.digest.update(&data[start..idx]);
+                self.digest.update(b"\r\n");
+                start = idx + 1;
+            }
+        }
+        self.digest.update(&data[start..]);
+
+        if let Some(last) = data.last() {
+            self.last_was_cr = *last == b'\r';
+        }
+    }
+
+    /// Appends the trailer of the signature and returns the digest
+    pub fn finalize(mut self, signature: &Signature) -> Vec<u8> {
+        self.digest.update(&signature.hashed_part);
+        self.digest.update(&[0x04, 0xff]);
+        self.digest
+            .update(&(signature.hashed_part.len() as u32).to_be_bytes());
+        self.digest.finalize().to_vec()
+    }
+}
+
+/// Parses all certificates of an exported public key or keyring, either ASCII armored or binary.
+/// Keys of other versions than 4 are skipped, as well as revoked keys and subkeys without valid binding.
+pub fn parse_certificates(content: &[u8]) -> Result<Vec<Certificate>> {
+    let mut certificates = vec![];
+    let mut current: Option<CertificatePackets> = None;
+    // the component of the certificate, which is signed by the following signatures
+    let mut component = Component::Other;
+
+    for (tag, body) in parse_packets(&dearmor(content)?)? {
+        match tag {
+            TAG_PUBLIC_KEY => {
+                certificates.extend(current.take());
+                current = parse_public_key(&body)?.map(|primary| CertificatePackets {
+                    primary,
+                    direct_signatures: vec![],
+                    user_ids: vec![],
+                    subkeys: vec![],
+                });
+                component = Component::Primary;
+            }
+            TAG_USER_ID => {
+                if let Some(packets) = current.as_mut() {
+                    packets.user_ids.push((body, vec![]));
+                    component = Component::UserId;
+                }
+            }
+            TAG_PUBLIC_SUBKEY => {
+                component = Component::Other;
+                if let Some(packets) = current.as_mut()
+                    && let Some(subkey) = parse_public_key(&body)?
+                {
+                    packets.subkeys.push((subkey, vec![]));
+                    component = Component::Subkey;
+                }
+            }
+            TAG_USER_ATTRIBUTE => component = Component::Other,
+            TAG_SIGNATURE => {
+                let Some(packets) = current.as_mut() else {
+                    continue;
+                };
+                // signatures, which can not be parsed, are not relevant for the trusted keys
+                let Ok(Some(signature)) = parse_signature(&body) else {
+                    continue;
+                };
+
+                let signatures = match component {
+                    Component::Primary => Some(&mut packets.direct_signatures),
+                    Component::UserId => packets.user_ids.last_mut().map(|(_, sigs)| sigs),
+                    Component::Subkey => packets.subkeys.last_mut().map(|(_, sigs)| sigs),
+                    Component::Other => None,
+                };
+                if let Some(signatures) = signatures {
+                    signatures.push(signature);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    certificates.extend(current);
+    Ok(certificates
+        .into_iter()
+        .filter_map(CertificatePackets::into_certificate)
+        .collect())
+}
+
+/// The part of a certificate, which is followed by its signatures
+enum Component {
+    Primary,
+    UserId,
+    Subkey,
+    Other,
+}
+
+/// The packets of a certificate, before the signatures are verified
+struct CertificatePackets {
+    primary: PublicKey,
+    /// direct-key signatures and revocations of the primary key
+    direct_signatures: Vec<Signature>,
+    user_ids: Vec<(Vec<u8>, Vec<Signature>)>,
+    subkeys: Vec<(PublicKey, Vec<Signature>)>,
+}
+
+impl CertificatePackets {
+    /// Verifies the self-signatures, `None` if the primary key is revoked
+    fn into_certificate(self) -> Option<Certificate> {
+        let mut primary = self.primary;
+        let primary_key = primary.hashed_key();
+        let fingerprint = hex::encode_upper(primary.fingerprint);
+
+        let mut self_signatures = vec![];
+        for signature in &self.direct_signatures {
+            match signature.sig_type {
+                SIG_KEY_REVOCATION if primary.verify_key_signature(signature, &[&primary_key]) => {
+                    log::warn!("Skip the revoked OpenPGP key {fingerprint}");
+                    return None;
+                }
+                SIG_DIRECT_KEY if primary.verify_key_signature(signature, &[&primary_key]) => {
+                    self_signatures.push(signature)
+                }
+                _ => {}
+            }
+        }
+
+        let mut user_id = None;
+        for (id, signatures) in &self.user_ids {
+            let hashed_id = [&[0xb4][..], &(id.len() as u32).to_be_bytes(), id].concat();
+            let certifications = signatures
+                .iter()
+                .filter(|signature| SIG_CERTIFICATIONS.contains(&signature.sig_type))
+                .filter(|signature| {
+                    primary.verify_key_signature(signature, &[&primary_key, &hashed_id])
+                })
+                .collect::<Vec<_>>();
+            if user_id.is_none() && !certifications.is_empty() {
+                user_id = Some(String::from_utf8_lossy(id).to_string());
+            }
+            self_signatures.extend(certifications);
+        }
+
+        // the newest self-signature defines the expiration of the primary key
+        primary.expiration = self_signatures
+            .into_iter()
+            .max_by_key(|signature| signature.creation_time)
+            .and_then(|signature| signature.key_expiration);
+
+        let subkeys = self
+            .subkeys
+            .into_iter()
+            .filter_map(|(subkey, signatures)| bind_subkey(&primary, subkey, &signatures))
+            .collect();
+
+        Some(Certificate {
+            primary,
+            user_id,
+            subkeys,
+        })
+    }
+}
+
+/// Returns the subkey, if it is bound to the primary key by a valid binding signature, is allowed
+/// to sign data, has a valid back-signature and is not revoked
+fn bind_subkey(
+    primary: &PublicKey,
+    mut subkey: PublicKey,
+    signatures: &[Signature],
+) -> Option<PublicKey> {
+    let hashed_keys = [primary.hashed_key(), subkey.hashed_key()];
+    let hashed_keys = [hashed_keys[0].as_slice(), hashed_keys[1].as_slice()];
+    let fingerprint = hex::encode_upper(subkey.fingerprint);
+
+    if signatures.iter().any(|signature| {
+        signature.sig_type == SIG_SUBKEY_REVOCATION
+            && primary.verify_key_signature(signature, &hashed_keys)
+    }) {
+        log::warn!("Skip the revoked OpenPGP subkey {fingerprint}");
+        return None;
+    }
+
+    let binding = signatures
+        .iter()
+        .filter(|signature| signature.sig_type == SIG_SUBKEY_BINDING)
+        .filter(|signature| primary.verify_key_signature(signature, &hashed_keys))
+        .max_by_key(|signature| signature.creation_time)?;
+    if !binding.allows_signing() {
+        return None;
+    }
+
+    // the subkey confirms the binding with a back-signature, otherwise anybody could bind a foreign subkey
+    let is_back_signed = binding
+        .embedded_signature
+        .as_ref()
+        .is_some_and(|back_signature| {
+            back_signature.sig_type == SIG_PRIMARY_KEY_BINDING
+                && subkey.verify_key_signature(back_signature, &hashed_keys)
+        });
+    if !is_back_signed {
+        log::warn!("Skip the OpenPGP subkey {fingerprint} - Missing valid back-signature");
+        return None;
+    }
+
+    subkey.expiration = binding.key_expiration;
+    Some(subkey)
+}
+
+/// Parses the signatures of a detached signature, either ASCII armored or binary.
+/// Unsupported signatures and signatures with the broken hash algorithm SHA-1 are skipped.
+pub fn parse_signatures(content: &[u8]) -> Result<Vec<Signature>> {
+    let signatures = parse_packets(&dearmor(content)?)?
+        .into_iter()
+        .filter(|(tag, _)| *tag == TAG_SIGNATURE)
+        .filter_map(|(_, body)| {
+            parse_signature(&body)
+                .inspect_err(|err| log::debug!("Skip OpenPGP signature - Details: {err}"))
+                .ok()
+                .flatten()
+        })
+        .filter(|signature| matches!(signature.sig_type, SIG_BINARY | SIG_TEXT))
+        .filter(|signature| {
+            let is_sha1 = signature.hash_algo == HashAlgorithm::Sha1;
+            if is_sha1 {
+                log::warn!("Skip OpenPGP signature - SHA-1 is not accepted for signed data");
+            }
+            !is_sha1
+        })
+        .collect::<Vec<_>>();
+
+    if signatures.is_empty() {
+        return Err(anyhow::anyhow!(
+            "The OpenPGP signature contains no supported signature of a document (version 4, not SHA-1)"
+        ));
+    }
+
+    Ok(signatures)
+}
+
+/// Removes the ASCII armor of all blocks, binary data is returned unchanged
+fn dearmor(content: &[u8]) -> Result<Vec<u8>> {
+    let text = match std::str::from_utf8(content) {
+        Ok(text) if text.contains("-----BEGIN PGP ") => text,
+        _ => return Ok(content.to_vec()),
+    };
+
+    let mut data = vec![];
+    let mut lines = text.lines().map(str::trim);
+
+    while lines.any(|line| line.starts_with("-----BEGIN PGP ")) {
+        let mut body = String::new();
+        let mut checksum = None;
+        let mut is_terminated = false;
+
+        // skip the armor headers, which are separated by an empty line from the body
+        for line in lines
+            .by_ref()
+            .skip_while(|line| line.contains(": ") || line.is_empty())
+        {
+            if line.starts_with("-----END PGP ") {
+                is_terminated = true;
+                break;
+            }
+            match line.strip_prefix('=') {
+                Some(crc) if crc.len() == 4 => checksum = Some(crc.to_string()),
+                _ => body.push_str(line),
+            }
+        }
+
+        if !is_terminated {
+            return Err(anyhow::anyhow!("Invalid ASCII armor - Missing END line"));
+        }
+
+        let block = STANDARD
+            .decode(&body)
+            .map_err(|err| anyhow::anyhow!("Invalid ASCII armor - {err}"))?;
+
+        // the checksum is optional
+        if let Some(checksum) = checksum {
+            let expected = STANDARD
+                .decode(&checksum)
+                .map_err(|err| anyhow::anyhow!("Invalid ASCII armor checksum - {err}"))?;
+            let calculated = Crc::<u32>::new(&CRC_24_OPENPGP).checksum(&block);
+            if expected != calculated.to_be_bytes()[1..] {
+                return Err(anyhow::anyhow!("Invalid ASCII armor - Checksum mismatch"));
+            }
+        }
+
+        data.extend(block);
+    }
+
+    Ok(data)
+}
+
+/// Splits the data into packets (tag and body), old and new packet headers are supported
+fn parse_packets(data: &[u8]) -> Result<Vec<(u8, Vec<u8>)>> {
+    let mut reader = Reader(data);
+    let mut packets = vec![];
+
+    while !reader.0.is_empty() {
+        let header = reader.u8()?;
+        if header & 0x80 == 0 {
+            return Err(malformed("packet header"));
+        }
+
+        let packet = if header & 0x40 != 0 {
+            let mut body = vec![];
+            loop {
+                let first = reader.u8()? as usize;
+                match first {
+                    0..=191 => {
+                        body.extend_from_slice(reader.take(first)?);
+                        break;
+                    }
+                    192..=223 => {
+                        let len = ((first - 192) << 8) + reader.u8()? as usize + 192;
+                        body.extend_from_slice(reader.take(len)?);
+                        break;
+                    }
+                    255 => {
+                        let len = reader.u32()? as usize;
+                        body.extend_from_slice(reader.take(len)?);
+                        break;
+                    }
+                    // partial body length, another length follows the chunk
+                    _ => body.extend_from_slice(reader.take(1 << (first & 0x1f))?),
+                }
+            }
+            (header & 0x3f, body)
+        } else {
+            let len = match header & 0x03 {
+                0 => reader.u8()? as usize,
+                1 => reader.u16()? as usize,
+                2 => reader.u32()? as usize,
+                // indeterminate length, the packet extends to the end of the data
+                _ => reader.0.len(),
+            };
+            ((header >> 2) & 0x0f, reader.take(len)?.to_vec())
+        };
+
+        packets.push(packet);
+    }
+
+    Ok(packets)
+}
+
+fn parse_public_key(body: &[u8]) -> Result<Option<PublicKey>> {
+    let mut reader = Reader(body);
+
+    let version = reader.u8()?;
+    if version != 4 {
+        log::debug!("Skip OpenPGP key of version {version}");
+        return Ok(None);
+    }
+    let creation_time = reader.u32()?;
+
+    let material = match reader.u8()? {
+        ALGO_RSA | ALGO_RSA_SIGN => {
+            let n = BigUint::from_bytes_be(reader.mpi()?);
+            let e = BigUint::from_bytes_be(reader.mpi()?);
+            let key = RsaPublicKey::new_with_max_size(n, e, MAX_RSA_KEY_BITS)
+                .map_err(|_| malformed("RSA key"))?;
+            KeyMaterial::Rsa(key)
+        }
+        ALGO_ECDSA => {
+            let oid = reader.oid()?;
+            let point = reader.mpi()?;
+            let invalid_point = |_| malformed("ECDSA key");
+            match oid {
+                OID_P256 => KeyMaterial::EcdsaP256(
+                    p256::ecdsa::VerifyingKey::from_sec1_bytes(point).map_err(invalid_point)?,
+                ),
+                OID_P384 => KeyMaterial::EcdsaP384(
+                    p384::ecdsa::VerifyingKey::from_sec1_bytes(point).map_err(invalid_point)?,
+                ),
+                OID_P521 => KeyMaterial::EcdsaP521(
+                    p521::ecdsa::VerifyingKey::from_sec1_bytes(point).map_err(invalid_point)?,
+                ),
+                _ => KeyMaterial::Unsupported(ALGO_ECDSA),
+            }
+        }
+        ALGO_EDDSA_LEGACY => {
+            let oid = reader.oid()?;
+            // the point is prefixed with 0x40
+            match (oid, reader.mpi()?) {
+                (OID_ED25519, [0x40, point @ ..]) => ed25519_key(point)?,
+                _ => KeyMaterial::Unsupported(ALGO_EDDSA_LEGACY),
+            }
+        }
+        ALGO_ED25519 => ed25519_key(reader.take(32)?)?,
+        algorithm => KeyMaterial::Unsupported(algorithm),
+    };
+
+    let fingerprint = sha1::Sha1::new()
+        .chain_update(key_hash_prefix(body))
+        .chain_update(body)
+        .finalize()
+        .into();
+
+    Ok(Some(PublicKey {
+        fingerprint,
+        creation_time,
+        expiration: None,
+        body: body.to_vec(),
+        material,
+    }))
+}
+
+fn ed25519_key(point: &[u8]) -> Result<KeyMaterial> {
+    let point: &[u8; 32] = point.try_into().map_err(|_| malformed("Ed25519 key"))?;
+    let key =
+        ed25519_dalek::VerifyingKey::from_bytes(point).map_err(|_| malformed("Ed25519 key"))?;
+    Ok(KeyMaterial::Ed25519(key))
+}
+
+/// A key is hashed with the prefix 0x99 and the two-octet length of the key packet
+fn key_hash_prefix(body: &[u8]) -> [u8; 3] {
+    let len = (body.len() as u16).to_be_bytes();
+    [0x99, len[0], len[1]]
+}
+
+fn parse_signature(body: &[u8]) -> Result<Option<Signature>> {
+    let mut reader = Reader(body);
+
+    let version = reader.u8()?;
+    if version != 4 {
+        log::debug!("Skip OpenPGP signature of version {version}");
+        return Ok(None);
+    }
+
+    let sig_type = reader.u8()?;
+    let pk_algo = reader.u8()?;
+    let hash_algo_id = reader.u8()?;
+    let Some(hash_algo) = HashAlgorithm::from_id(hash_algo_id) else {
+        log::debug!("Skip OpenPGP signature with unsupported hash algorithm {hash_algo_id}");
+        return Ok(None);
+    };
+
+    let hashed_len = reader.u16()? as usize;
+    let hashed_subpackets = reader.take(hashed_len)?;
+    let hashed_part = body[..6 + hashed_len].to_vec();
+    let unhashed_len = reader.u16()? as usize;
+    let unhashed_subpackets = reader.take(unhashed_len)?;
+
+    let mut left16 = [0u8; 2];
+    left16.copy_from_slice(reader.take(2)?);
+
+    let values = match pk_algo {
+        ALGO_RSA | ALGO_RSA_SIGN => vec![reader.mpi()?.to_vec()],
+        ALGO_ED25519 => vec![reader.take(64)?.to_vec()],
+        _ => vec![reader.mpi()?.to_vec(), reader.mpi()?.to_vec()],
+    };
+
+    let mut signature = Signature {
+        sig_type,
+        pk_algo,
+        hash_algo,
+        hashed_part,
+        left16,
+        issuer_key_id: None,
+        issuer_fingerprint: None,
+        key_flags: None,
+        creation_time: None,
+        expiration: None,
+        key_expiration: None,
+        embedded_signature: None,
+        values,
+    };
+
+    for (is_hashed, subpackets) in [(true, hashed_subpackets), (false, unhashed_subpackets)] {
+        for (subpacket_type, data) in parse_subpackets(subpackets)? {
+            match (subpacket_type, data) {
+                (SUBPACKET_ISSUER, key_id) if key_id.len() == 8 => {
+                    signature.issuer_key_id = Some(key_id.to_vec())
+                }
+                (SUBPACKET_ISSUER_FINGERPRINT, [4, fingerprint @ ..]) => {
+                    signature.issuer_fingerprint = Some(fingerprint.to_vec())
+                }
+                // flags in the unhashed area are not protected by the signature
+                (SUBPACKET_KEY_FLAGS, [key_flags, ..]) if is_hashed => {
+                    signature.key_flags = Some(*key_flags)
+                }
+                (SUBPACKET_CREATION_TIME, time) if is_hashed => {
+                    signature.creation_time = Some(Reader(time).u32()?)
+                }
+                (SUBPACKET_EXPIRATION_TIME, time) if is_hashed => {
+                    signature.expiration = Some(Reader(time).u32()?)
+                }
+                (SUBPACKET_KEY_EXPIRATION_TIME, time) if is_hashed => {
+                    signature.key_expiration = Some(Reader(time).u32()?)
+                }
+                // the back-signature is protected by its own signature
+                (SUBPACKET_EMBEDDED_SIGNATURE, body) => {
+                    signature.embedded_signature = parse_signature(body)?.map(Box::new)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Some(signature))
+}
+
+/// Returns the type (without the critical bit) and the data of the subpackets
+fn parse_subpackets(data: &[u8]) -> Result<Vec<(u8, &[u8])>> {
+    let mut reader = Reader(data);
+    let mut subpackets = vec![];
+
+    while !reader.0.is_empty() {
+        let first = reader.u8()? as usize;
+        let len = match first {
+            0..=191 => first,
+            192..=254 => ((first - 192) << 8) + reader.u8()? as usize + 192,
+            _ => reader.u32()? as usize,
+        };
+        match reader.take(len)? {
+            [subpacket_type, data @ ..] => subpackets.push((subpacket_type & 0x7f, data)),
+            [] => return Err(malformed("signature subpacket")),
+        }
+    }
+
+    Ok(subpackets)
+}
+
+/// Left-pads a big-endian number with zeros, `None` if the number is longer
+fn left_pad(value: &[u8], len: usize) -> Option<Vec<u8>> {
+    let padding = len.checked_sub(value.len())?;
+    let mut padded = vec![0u8; padding];
+    padded.extend_from_slice(value);
+    Some(padded)
+}
+
+fn malformed(part: &str) -> anyhow::Error {
+    anyhow::anyhow!("Invalid OpenPGP data - Malformed {part}")
+}
+
+/// Reads the fields of a packet
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.0.len() {
+            return Err(malformed("packet"));
+        }
+        let (value, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(value)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    /// A multiprecision integer: the length in bits followed by the big-endian number
+    fn mpi(&mut self) -> Result<&'a [u8]> {
+        let bits = self.u16()? as usize;
+        self.take(bits.div_ceil(8))
+    }
+
+    /// A curve OID: the length followed by the encoded OID
+    fn oid(&mut self) -> Result<&'a [u8]> {
+        let len = self.u8()? as usize;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dearmor() {
+        let armored = "-----BEGIN PGP PUBLIC KEY BLOCK-----\nComment: first key\n\nSGVsbG8g\n=u9Ng\n-----END PGP PUBLIC KEY BLOCK-----\n\
+            -----BEGIN PGP PUBLIC KEY BLOCK-----\n\nV29ybGQ=\n-----END PGP PUBLIC KEY BLOCK-----\n";
+        // all blocks are decoded, the checksum is optional
+        assert_eq!(
+            dearmor(armored.as_bytes()).unwrap(),
+            b"Hello World".to_vec()
+        );
+
+        let wrong_checksum = armored.replace("=u9Ng", "=gU+e");
+        assert!(dearmor(wrong_checksum.as_bytes()).is_err());
+
+        let unterminated = "-----BEGIN PGP SIGNATURE-----\n\nSGVsbG8g\n";
+        assert!(dearmor(unterminated.as_bytes()).is_err());
+
+        assert_eq!(dearmor(&[0x99, 0x01]).unwrap(), vec![0x99, 0x01]);
+    }
+
+    #[test]
+    fn test_parse_packets() {
+        // new format, old format and new format with a partial body length
+        let data = [
+            0xcd, 0x03, b'a', b'b', b'c', 0x88, 0x01, 0x05, 0xcb, 0xe1, b'a', b'b', 0x01, b'c',
+        ];
+        assert_eq!(
+            parse_packets(&data).unwrap(),
+            vec![
+                (TAG_USER_ID, b"abc".to_vec()),
+                (TAG_SIGNATURE, vec![0x05]),
+                (11, b"abc".to_vec())
+            ]
+        );
+
+        assert!(parse_packets(&[0x00]).is_err());
+        assert!(parse_packets(&[0xcd, 0x05, b'a']).is_err());
+    }
+
+    #[test]
+    fn test_text_mode_hasher() {
+        let mut hasher = SignatureHasher::new(HashAlgorithm::Sha256, true);
+        for chunk in [&b"a"[..], b"\n", b"b\r", b"\nc\n"] {
+            hasher.update(chunk);
+        }
+        assert_eq!(
+            hasher.digest.finalize().to_vec(),
+            sha2::Sha256::digest(b"a\r\nb\r\nc\r\n").to_vec()
+        );
+    }
+    const CREATED: u32 = 1_700_000_000;
+    const HASH_SHA1: u8 = 2;
+    const HASH_SHA256: u8 = 8;
+    const HASH_SHA3_256: u8 = 12;
+
+    /// An Ed25519 key and its packet body
+    struct TestKey {
+        signing_key: ed25519_dalek::SigningKey,
+        body: Vec<u8>,
+    }
+
+    fn test_key(seed: u8) -> TestKey {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[seed; 32]);
+        let body = [
+            &[4][..],
+            &CREATED.to_be_bytes(),
+            &[ALGO_ED25519],
+            signing_key.verifying_key().as_bytes(),
+        ]
+        .concat();
+        TestKey { signing_key, body }
+    }
+
+    fn hashed(key: &TestKey) -> Vec<u8> {
+        [&key_hash_prefix(&key.body)[..], &key.body].concat()
+    }
+
+    fn packet(tag: u8, body: &[u8]) -> Vec<u8> {
+        let len = (body.len() as u32).to_be_bytes();
+        [&[0xc0 | tag, 0xff][..], &len, body].concat()
+    }
+
+    fn subpacket(subpacket_type: u8, data: &[u8]) -> Vec<u8> {
+        [&[data.len() as u8 + 1, subpacket_type][..], data].concat()
+    }
+
+    fn created(time: u32) -> Vec<u8> {
+        subpacket(SUBPACKET_CREATION_TIME, &time.to_be_bytes())
+    }
+
+    /// Returns the body of a signature of the key over the hashed data, the issuer is added to the subpackets
+    fn sign(
+        key: &TestKey,
+        sig_type: u8,
+        hash_id: u8,
+        subpackets: &[Vec<u8>],
+        hashed_data: &[&[u8]],
+    ) -> Vec<u8> {
+        let fingerprint = sha1::Sha1::digest(hashed(key));
+        let issuer = subpacket(
+            SUBPACKET_ISSUER_FINGERPRINT,
+            &[&[4][..], &fingerprint].concat(),
+        );
+        let subpackets = [&[issuer][..], subpackets].concat().concat();
+        let hashed_part = [
+            &[4, sig_type, ALGO_ED25519, hash_id][..],
+            &(subpackets.len() as u16).to_be_bytes(),
+            &subpackets,
+        ]
+        .concat();
+
+        let digest = match HashAlgorithm::from_id(hash_id) {
+            Some(hash_algo) => {
+                let mut hasher = SignatureHasher::new(hash_algo, false);
+                hashed_data.iter().for_each(|data| hasher.update(data));
+                hasher.update(&hashed_part);
+                hasher.update(&[0x04, 0xff]);
+                hasher.update(&(hashed_part.len() as u32).to_be_bytes());
+                hasher.digest.finalize().to_vec()
+            }
+            None => vec![0; 32],
+        };
+        let signature = ed25519_dalek::Signer::sign(&key.signing_key, &digest);
+
+        [
+            &hashed_part[..],
+            &[0, 0],
+            &digest[..2],
+            &signature.to_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Returns the primary key with a user ID and a self-signature
+    fn primary_packets(primary: &TestKey, self_subpackets: &[Vec<u8>]) -> Vec<u8> {
+        let user_id = b"Alice <alice@example.org>";
+        let hashed_id = [&[0xb4, 0, 0, 0, user_id.len() as u8][..], user_id].concat();
+        let self_signature = sign(
+            primary,
+            0x13,
+            HASH_SHA256,
+            &[&[created(CREATED)][..], self_subpackets].concat(),
+            &[&hashed(primary), &hashed_id],
+        );
+        [
+            packet(TAG_PUBLIC_KEY, &primary.body),
+            packet(TAG_USER_ID, user_id),
+            packet(TAG_SIGNATURE, &self_signature),
+        ]
+        .concat()
+    }
+
+    /// Returns the subkey with a signing binding, the back-signature is made by the given key
+    fn subkey_packets(
+        primary: &TestKey,
+        subkey: &TestKey,
+        back_signer: Option<&TestKey>,
+    ) -> Vec<u8> {
+        let keys = [hashed(primary), hashed(subkey)];
+        let keys = [keys[0].as_slice(), keys[1].as_slice()];
+        let mut subpackets = vec![created(CREATED), subpacket(SUBPACKET_KEY_FLAGS, &[0x02])];
+        if let Some(back_signer) = back_signer {
+            let back_signature = sign(
+                back_signer,
+                SIG_PRIMARY_KEY_BINDING,
+                HASH_SHA256,
+                &[created(CREATED)],
+                &keys,
+            );
+            subpackets.push(subpacket(SUBPACKET_EMBEDDED_SIGNATURE, &back_signature));
+        }
+        let binding = sign(primary, SIG_SUBKEY_BINDING, HASH_SHA256, &subpackets, &keys);
+
+        [
+            packet(TAG_PUBLIC_SUBKEY, &subkey.body),
+            packet(TAG_SIGNATURE, &binding),
+        ]
+        .concat()
+    }
+
+    fn fingerprints(certificates: &[Certificate]) -> Vec<String> {
+        certificates
+            .iter()
+            .flat_map(|certificate| certificate.keys())
+            .map(|key| hex::encode_upper(key.fingerprint))
+            .collect()
+    }
+
+    fn fingerprint(key: &TestKey) -> String {
+        hex::encode_upper(sha1::Sha1::digest(hashed(key)))
+    }
+
+    #[test]
+    fn test_parse_signatures_skips_unsupported() {
+        let key = test_key(1);
+        let document = sign(
+            &key,
+            SIG_BINARY,
+            HASH_SHA256,
+            &[created(CREATED)],
+            &[b"data"],
+        );
+        let sha1_document = sign(&key, SIG_BINARY, HASH_SHA1, &[created(CREATED)], &[b"data"]);
+        let unknown_hash = sign(
+            &key,
+            SIG_BINARY,
+            HASH_SHA3_256,
+            &[created(CREATED)],
+            &[b"data"],
+        );
+        let truncated = &document[..10];
+
+        // the supported signature is found besides the unsupported ones
+        let content = [
+            packet(TAG_SIGNATURE, &unknown_hash),
+            packet(TAG_SIGNATURE, truncated),
+            packet(TAG_SIGNATURE, &sha1_document),
+            packet(TAG_SIGNATURE, &document),
+        ]
+        .concat();
+        let signatures = parse_signatures(&content).unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].hash_algo, HashAlgorithm::Sha256);
+
+        // SHA-1 is rejected for signed data
+        assert!(parse_signatures(&packet(TAG_SIGNATURE, &sha1_document)).is_err());
+        assert!(parse_signatures(&packet(TAG_SIGNATURE, &unknown_hash)).is_err());
+    }
+
+    #[test]
+    fn test_check_validity() {
+        let key = test_key(1);
+        let key_expiration = subpacket(SUBPACKET_KEY_EXPIRATION_TIME, &100u32.to_be_bytes());
+        let certificates = parse_certificates(&primary_packets(&key, &[key_expiration])).unwrap();
+        let certificate = &certificates[0];
+        let primary = &certificate.primary;
+        assert_eq!(primary.expiration_time(), Some(u64::from(CREATED) + 100));
+
+        let check = |subpackets: &[Vec<u8>], now: u32| {
+            let body = sign(&key, SIG_BINARY, HASH_SHA256, subpackets, &[b"data"]);
+            let signature = parse_signature(&body).unwrap().unwrap();
+            certificate.check_validity(primary, &signature, u64::from(now))
+        };
+
+        assert!(check(&[created(CREATED + 50)], CREATED + 1000).is_ok());
+        // the key expired before the signature was created
+        assert!(check(&[created(CREATED + 100)], CREATED + 1000).is_err());
+        assert!(check(&[created(CREATED - 1)], CREATED + 1000).is_err());
+        assert!(check(&[created(CREATED + 50)], CREATED + 10).is_err());
+        assert!(check(&[], CREATED + 1000).is_err());
+
+        // the signature itself expires
+        let expiration = subpacket(SUBPACKET_EXPIRATION_TIME, &10u32.to_be_bytes());
+        let subpackets = [created(CREATED + 50), expiration];
+        assert!(check(&subpackets, CREATED + 59).is_ok());
+        assert!(check(&subpackets, CREATED + 60).is_err());
+    }
+
+    #[test]
+    fn test_revocations() {
+        let (primary, subkey) = (test_key(1), test_key(2));
+        let certificate = [
+            primary_packets(&primary, &[]),
+            subkey_packets(&primary, &subkey, Some(&subkey)),
+        ]
+        .concat();
+        assert_eq!(
+            fingerprints(&parse_certificates(&certificate).unwrap()),
+            vec![fingerprint(&primary), fingerprint(&subkey)]
+        );
+
+        let key_revocation = sign(
+            &primary,
+            SIG_KEY_REVOCATION,
+            HASH_SHA256,
+            &[created(CREATED)],
+            &[&hashed(&primary)],
+        );
+        let revoked_primary = [
+            packet(TAG_PUBLIC_KEY, &primary.body),
+            packet(TAG_SIGNATURE, &key_revocation),
+            certificate[packet(TAG_PUBLIC_KEY, &primary.body).len()..].to_vec(),
+        ]
+        .concat();
+        assert!(parse_certificates(&revoked_primary).unwrap().is_empty());
+
+        let subkey_revocation = sign(
+            &primary,
+            SIG_SUBKEY_REVOCATION,
+            HASH_SHA256,
+            &[created(CREATED)],
+            &[&hashed(&primary), &hashed(&subkey)],
+        );
+        let revoked_subkey = [
+            certificate.clone(),
+            packet(TAG_SIGNATURE, &subkey_revocation),
+        ]
+        .concat();
+        assert_eq!(
+            fingerprints(&parse_certificates(&revoked_subkey).unwrap()),
+            vec![fingerprint(&primary)]
+        );
+
+        // a revocation made by another key is ignored
+        let foreign_revocation = sign(
+            &subkey,
+            SIG_SUBKEY_REVOCATION,
+            HASH_SHA256,
+            &[created(CREATED)],
+            &[&hashed(&primary), &hashed(&subkey)],
+        );
+        let foreign_revoked = [certificate, packet(TAG_SIGNATURE, &foreign_revocation)].concat();
+        assert_eq!(
+            parse_certificates(&foreign_revoked).unwrap()[0]
+                .subkeys
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_subkey_back_signature() {
+        let (primary, subkey, other) = (test_key(1), test_key(2), test_key(3));
+        let parse = |back_signer: Option<&TestKey>| {
+            let content = [
+                primary_packets(&primary, &[]),
+                subkey_packets(&primary, &subkey, back_signer),
+            ]
+            .concat();
+            parse_certificates(&content).unwrap()
+        };
+
+        assert!(parse(None)[0].subkeys.is_empty());
+        assert!(parse(Some(&other))[0].subkeys.is_empty());
+
+        let certificates = parse(Some(&subkey));
+        let certificate = &certificates[0];
+        assert_eq!(
+            certificate.user_id.as_deref(),
+            Some("Alice <alice@example.org>")
+        );
+
+        // the subkey verifies a signature of a document
+        let body = sign(
+            &subkey,
+            SIG_BINARY,
+            HASH_SHA256,
+            &[created(CREATED)],
+            &[b"data"],
+        );
+        let signature = parse_signature(&body).unwrap().unwrap();
+        let key = certificate
+            .keys()
+            .find(|key| signature.is_issued_by(key))
+            .unwrap();
+        assert_eq!(hex::encode_upper(key.fingerprint), fingerprint(&subkey));
+
+        let mut hasher = signature.hasher();
+        hasher.update(b"data");
+        assert!(key.verify(&signature, &hasher.finalize(&signature)).is_ok());
+        assert!(
+            certificate
+                .check_validity(key, &signature, u64::from(CREATED))
+                .is_ok()
+        );
+    }
+}
@@ -11,7 +11,7 @@ pub const UNIX_INVALID_FILE_NAME_CHARS: &str = r":/\\";
 pub const WINDOWS_INVALID_FILE_NAME_CHARS: &str = r#"<>:"/\\|?*"#;
 
 /// Supported Operating-Systems
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
 pub enum OS {
     Linux,
     MacOs,
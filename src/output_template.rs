@@ -0,0 +1,318 @@
+use std::str::FromStr;
+
+use crate::{command_handling::CommandResult, utils};
+
+/// The placeholders of a template and their description
+pub const PLACEHOLDERS: [(&str, &str); 20] = [
+    ("hash", "the calculated hash sum"),
+    ("algorithm", "the used algorithm, e.g. SHA2-256"),
+    (
+        "input",
+        "the hashed file, the URL of a download that was not saved or '-' for a buffer",
+    ),
+    ("path", "the location of the hashed file"),
+    ("buffer", "the hashed buffer"),
+    ("given_hash", "the hash that was compared"),
+    ("match", "'match' or 'mismatch', if a hash was compared"),
+    ("signature", "the scheme of a valid signature, e.g. OpenPGP"),
+    ("signer", "the key ID of the signer"),
+    ("signed_object", "the file or buffer that was signed"),
+    (
+        "download_source",
+        "the URL from which the file was downloaded",
+    ),
+    (
+        "pieces",
+        "the number of valid and of all pieces of a Metalink file, e.g. 12/12",
+    ),
+    (
+        "server_digests",
+        "the results of the digests advertised by the server",
+    ),
+    ("redirects", "the redirect chain of the download"),
+    (
+        "cached",
+        "'true' if the file of a previous download was reused",
+    ),
+    ("size", "the number of hashed bytes"),
+    (
+        "elapsed",
+        "the duration of the download or the hash calculation in seconds",
+    ),
+    ("final_url", "the URL after all redirects"),
+    ("files", "the number of hashed files of a directory"),
+    ("headers", "the headers of the server response"),
+];
+
+/// A template for the output of a command result, e.g. `{hash}  {path}`.
+/// Use `{{` and `}}` for literal braces, `\n` and `\t` for a line break and a tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    /// the name of the placeholder
+    Placeholder(&'static str),
+}
+
+impl FromStr for OutputTemplate {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(char) => name.push(char),
+                            None => return Err(format!("unclosed placeholder '{{{name}'")),
+                        }
+                    }
+
+                    let placeholder = PLACEHOLDERS
+                        .iter()
+                        .map(|(placeholder, _)| *placeholder)
+                        .find(|placeholder| *placeholder == name.trim())
+                        .ok_or_else(|| {
+                            format!(
+                                "unknown placeholder '{{{name}}}', supported placeholders: {}",
+                                placeholder_names()
+                            )
+                        })?;
+
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Placeholder(placeholder));
+                }
+                '}' => return Err("unmatched '}', use '}}' for a literal brace".to_string()),
+                '\\' => match chars.peek() {
+                    Some('n') => {
+                        chars.next();
+                        text.push('\n');
+                    }
+                    Some('t') => {
+                        chars.next();
+                        text.push('\t');
+                    }
+                    Some('\\') => {
+                        chars.next();
+                        text.push('\\');
+                    }
+                    _ => text.push('\\'),
+                },
+                _ => text.push(char),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl OutputTemplate {
+    /// Replaces the placeholders with the values of the command result, missing values are empty
+    pub fn render(&self, cmd_result: &CommandResult) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Placeholder(name) => placeholder_value(name, cmd_result),
+            })
+            .collect()
+    }
+}
+
+/// The help text of the option [--format] with the description of all placeholders
+pub fn help() -> String {
+    let placeholders = PLACEHOLDERS
+        .iter()
+        .map(|(name, description)| format!("  {{{name}}}: {description}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "Print the result with a template instead of the default layout, e.g. '{{hash}}  {{input}}' or '{{algorithm}}:{{hash}}'.\n\
+         Use '{{{{' and '}}}}' for literal braces, '\\n' and '\\t' for a line break and a tab. Placeholders:\n{placeholders}"
+    )
+}
+
+/// Returns the names of all placeholders, e.g. `{hash}, {algorithm}, ...`
+pub fn placeholder_names() -> String {
+    PLACEHOLDERS
+        .iter()
+        .map(|(name, _)| format!("{{{name}}}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn placeholder_value(name: &str, cmd_result: &CommandResult) -> String {
+    let details = &cmd_result.details;
+
+    match name {
+        "hash" => cmd_result.calculated_hash_sum.clone(),
+        "algorithm" => cmd_result.used_algorithm.to_string(),
+        "input" => match (&cmd_result.file_location, &cmd_result.buffer) {
+            (Some(file_location), _) => utils::absolute_path_as_string(file_location),
+            (None, Some(_)) => "-".to_string(),
+            (None, None) => cmd_result.download_source.clone().unwrap_or_default(),
+        },
+        "path" => cmd_result
+            .file_location
+            .as_deref()
+            .map(utils::absolute_path_as_string)
+            .unwrap_or_default(),
+        "buffer" => cmd_result.buffer.clone().unwrap_or_default(),
+        "given_hash" => cmd_result
+            .hash_compare_result
+            .as_ref()
+            .map(|compare_result| compare_result.given_hash.clone())
+            .unwrap_or_default(),
+        "match" => match &cmd_result.hash_compare_result {
+            Some(compare_result) if compare_result.is_equal => "match".to_string(),
+            Some(_) => "mismatch".to_string(),
+            None => String::new(),
+        },
+        "signature" => cmd_result
+            .signature_verification
+            .as_ref()
+            .map(|verification| verification.scheme.to_string())
+            .unwrap_or_default(),
+        "signer" => cmd_result
+            .signature_verification
+            .as_ref()
+            .map(|verification| verification.signer_key_id.clone())
+            .unwrap_or_default(),
+        "signed_object" => cmd_result
+            .signature_verification
+            .as_ref()
+            .map(|verification| verification.signed_object.clone())
+            .unwrap_or_default(),
+        "download_source" => cmd_result.download_source.clone().unwrap_or_default(),
+        "pieces" => cmd_result
+            .piece_verification
+            .as_ref()
+            .map(|verification| {
+                format!(
+                    "{}/{}",
+                    verification.total - verification.corrupted.len(),
+                    verification.total
+                )
+            })
+            .unwrap_or_default(),
+        "server_digests" => cmd_result
+            .integrity_checks
+            .iter()
+            .map(|check| {
+                format!(
+                    "{} ({}): {}",
+                    check.header,
+                    check.algorithm,
+                    if check.is_valid { "match" } else { "mismatch" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        "redirects" => cmd_result.redirect_chain.join(" -> "),
+        "cached" => cmd_result.from_cache.to_string(),
+        "size" => details
+            .size
+            .map(|size| size.to_string())
+            .unwrap_or_default(),
+        "elapsed" => details
+            .elapsed
+            .map(|elapsed| format!("{:.3}", elapsed.as_secs_f64()))
+            .unwrap_or_default(),
+        "final_url" => details.final_url.clone().unwrap_or_default(),
+        "files" => details
+            .walked_files
+            .map(|walked_files| walked_files.to_string())
+            .unwrap_or_default(),
+        "headers" => details
+            .response_headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect::<Vec<_>>()
+            .join("; "),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        command_handling::{HashCompareResult, ResultDetails},
+        hasher::{self, Algorithm},
+    };
+
+    const HASH: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn test_render_template() {
+        let cmd_result = CommandResult {
+            file_location: None,
+            buffer: Some("hello".to_string()),
+            used_algorithm: Algorithm::SHA2_256,
+            calculated_hash_sum: HASH.to_string(),
+            hash_compare_result: Some(HashCompareResult {
+                is_equal: false,
+                given_hash: "2cf24dbb".to_string(),
+            }),
+            signature_verification: None,
+            download_source: None,
+            piece_verification: None,
+            integrity_checks: vec![],
+            redirect_chain: vec![],
+            from_cache: false,
+            details: ResultDetails {
+                size: Some(5),
+                ..Default::default()
+            },
+        };
+
+        let render = |template: &str| {
+            template
+                .parse::<OutputTemplate>()
+                .unwrap()
+                .render(&cmd_result)
+        };
+
+        assert_eq!(render("{hash}  {input}"), format!("{HASH}  -"));
+        assert_eq!(render("{algorithm}:{hash}"), format!("SHA2-256:{HASH}"));
+        assert_eq!(
+            render("{match}\\t{ given_hash }\\n{size} bytes{signer}"),
+            "mismatch\t2cf24dbb\n5 bytes"
+        );
+        assert_eq!(render("{{hash}} \\d"), "{hash} \\d");
+
+        // the prefixed hash is accepted as input again
+        let hash_property = hasher::parse_hash(&render("{algorithm}:{hash}")).unwrap();
+        assert_eq!(hash_property.algorithm, Some(Algorithm::SHA2_256));
+        assert_eq!(hash_property.hash, HASH);
+
+        assert!("{unknown}".parse::<OutputTemplate>().is_err());
+        assert!("{hash".parse::<OutputTemplate>().is_err());
+        assert!("hash}".parse::<OutputTemplate>().is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     app::{APP_NAME, data_dir, set_rust_backtrace},
-    term_output, utils,
+    term_output::{self, ColorMode},
+    utils,
 };
 use anyhow::{Context, Result};
 use std::{
@@ -15,7 +16,7 @@ use termcolor::{Color, ColorSpec, WriteColor};
 /// Define a custom panic hook to handle a application crash.
 /// Try to reset the terminal properties in case of the application panicked (crashed).
 /// This way, you won't have your terminal messed up if an unexpected error happens.
-pub fn initialize_panic_hook(no_color: bool) -> Result<()> {
+pub fn initialize_panic_hook(color: ColorMode) -> Result<()> {
     // set the RUST_BACKTRACE environment variable to 1
     set_rust_backtrace();
     // set the custom panic hook handler
@@ -24,7 +25,7 @@ pub fn initialize_panic_hook(no_color: bool) -> Result<()> {
         let crash_report_file = crash_report_file();
 
         let backtrace = std::backtrace::Backtrace::capture();
-        let panic_report = PanicReport::new(panic_info, backtrace, no_color);
+        let panic_report = PanicReport::new(panic_info, backtrace, color);
         if let Err(err) = panic_report.write_report_and_print_msg(&crash_report_file) {
             log::error!("{err}");
             eprintln!("{err}")
@@ -129,7 +130,7 @@ impl std::fmt::Display for CargoMetadata {
 pub struct PanicReport<'a> {
     panic_info: &'a PanicHookInfo<'a>,
     backtrace: Backtrace,
-    no_color: bool,
+    color: ColorMode,
 }
 
 /// A human readable crash report
@@ -170,11 +171,11 @@ impl HumanReadableReport {
 
 impl<'a> PanicReport<'a> {
     /// Constructs a new instance of [`PanicReport`].
-    pub fn new(panic_info: &'a PanicHookInfo, backtrace: Backtrace, no_color: bool) -> Self {
+    pub fn new(panic_info: &'a PanicHookInfo, backtrace: Backtrace, color: ColorMode) -> Self {
         Self {
             panic_info,
             backtrace,
-            no_color,
+            color,
         }
     }
 
@@ -198,7 +199,7 @@ impl<'a> PanicReport<'a> {
 
         let path_to_crash_report = utils::absolute_path_as_string(p);
 
-        let mut stdout = term_output::get_stdout(self.no_color);
+        let mut stdout = term_output::get_stdout(self.color);
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
 
         writeln!(
@@ -0,0 +1,715 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use sha2::Digest;
+use signature::Verifier;
+use ssh_key::{HashAlg, PublicKey, SshSig};
+
+use crate::{
+    download,
+    http_client::{self, HttpOptions},
+    openpgp, utils,
+};
+
+/// Namespace that is used by default when verifying SSH signatures (`ssh-keygen -Y sign -n file`)
+pub const DEFAULT_SSH_NAMESPACE: &str = "file";
+
+// Max. size of a detached signature or a checksum file that is loaded into memory
+const MAX_SOURCE_SIZE: usize = 1024 * 1024;
+
+const PGP_SIGNATURE_HEADER: &str = "-----BEGIN PGP SIGNATURE-----";
+const SSH_SIGNATURE_HEADER: &str = "-----BEGIN SSH SIGNATURE-----";
+const MINISIGN_COMMENT_PREFIX: &str = "untrusted comment:";
+
+/// Supported formats of detached signatures
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// OpenPGP signature (`.asc`, `.sig`)
+    OpenPgp,
+    /// Minisign signature (`.minisig`)
+    Minisign,
+    /// SSH signature created by `ssh-keygen -Y sign`
+    Ssh,
+}
+
+impl std::fmt::Display for SignatureScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SignatureScheme::OpenPgp => write!(f, "OpenPGP"),
+            SignatureScheme::Minisign => write!(f, "minisign"),
+            SignatureScheme::Ssh => write!(f, "SSH"),
+        }
+    }
+}
+
+/// Everything needed to verify a detached signature
+#[derive(Debug, Clone)]
+pub struct SignatureProperties {
+    /// URL or path of the detached signature
+    pub source: String,
+    /// A single trusted public key
+    pub pubkey: Option<PathBuf>,
+    /// A file containing several trusted public keys
+    pub keyring: Option<PathBuf>,
+    /// Namespace of SSH signatures
+    pub namespace: String,
+}
+
+/// Error type for a signature, which is not valid for any of the trusted keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSignatureError {
+    scheme: SignatureScheme,
+}
+
+impl Error for InvalidSignatureError {}
+
+impl std::fmt::Display for InvalidSignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The {} signature is NOT valid for any of the given public keys",
+            self.scheme
+        )
+    }
+}
+
+/// The result of a successful signature verification
+#[derive(Debug, Clone)]
+pub struct SignatureVerification {
+    pub scheme: SignatureScheme,
+    pub signer_key_id: String,
+    pub signed_object: String,
+}
+
+/// Detects the signature format based on the content of the signature
+pub fn detect_scheme(signature: &[u8]) -> Option<SignatureScheme> {
+    let text = String::from_utf8_lossy(signature);
+    let text = text.trim_start();
+
+    if text.starts_with(MINISIGN_COMMENT_PREFIX) {
+        Some(SignatureScheme::Minisign)
+    } else if text.starts_with(SSH_SIGNATURE_HEADER) {
+        Some(SignatureScheme::Ssh)
+    } else if text.starts_with(PGP_SIGNATURE_HEADER) {
+        Some(SignatureScheme::OpenPgp)
+    } else if signature.first().is_some_and(|b| b & 0x80 != 0) {
+        // binary OpenPGP packets always have the most significant bit of the first byte set
+        Some(SignatureScheme::OpenPgp)
+    } else {
+        None
+    }
+}
+
+/// Verifies the detached signature against the file under the given path
+pub fn verify_file(
+    properties: &SignatureProperties,
+    path: &Path,
+    http_options: &HttpOptions,
+) -> Result<SignatureVerification> {
+    let file = File::open(path).map_err(|io_err| {
+        let msg = format!(
+            "Failed to open file for signature verification: {}",
+            utils::absolute_path_as_string(path)
+        );
+        log::error!("{msg} - Details: {io_err:?}");
+        anyhow::anyhow!(msg)
+    })?;
+
+    verify(
+        properties,
+        BufReader::with_capacity(utils::CAPACITY, file),
+        utils::absolute_path_as_string(path),
+        http_options,
+    )
+}
+
+/// Verifies the detached signature against the given byte buffer
+pub fn verify_buffer(
+    properties: &SignatureProperties,
+    buffer: &[u8],
+    signed_object: String,
+    http_options: &HttpOptions,
+) -> Result<SignatureVerification> {
+    verify(properties, buffer, signed_object, http_options)
+}
+
+/// Loads the detached signature, determines its format and verifies the data of the reader
+fn verify(
+    properties: &SignatureProperties,
+    data: impl Read,
+    signed_object: String,
+    http_options: &HttpOptions,
+) -> Result<SignatureVerification> {
+    if properties.pubkey.is_none() && properties.keyring.is_none() {
+        return Err(anyhow::anyhow!(
+            "A public key [--pubkey] or a keyring [--keyring] is required to verify the signature"
+        ));
+    }
+
+    // credentials of the URL must not be written to the log file
+    let source = http_client::redact_url(&properties.source);
+    let signature = load_source(&properties.source, http_options)
+        .with_context(|| format!("Failed to load signature: {source}"))?;
+
+    let scheme = detect_scheme(&signature).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown signature format: {source} - Supported are OpenPGP, minisign and SSH signatures"
+        )
+    })?;
+
+    log::info!(
+        "Verify {scheme} signature '{source}' against: {}",
+        http_client::redact_url(&signed_object)
+    );
+
+    let trusted_keys = properties
+        .pubkey
+        .iter()
+        .chain(properties.keyring.iter())
+        .map(|key_file| {
+            fs::read(key_file).with_context(|| {
+                format!(
+                    "Failed to read public key file: {}",
+                    utils::absolute_path_as_string(key_file)
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let signer_key_id = match scheme {
+        SignatureScheme::OpenPgp => verify_openpgp(&trusted_keys, &signature, data)?,
+        SignatureScheme::Minisign => verify_minisign(&trusted_keys, &signature, data)?,
+        SignatureScheme::Ssh => verify_ssh(&trusted_keys, &signature, &properties.namespace, data)?,
+    };
+
+    log::info!("Valid {scheme} signature - Signer key: {signer_key_id}");
+
+    Ok(SignatureVerification {
+        scheme,
+        signer_key_id,
+        signed_object,
+    })
+}
+
+/// Loads a signature or a checksum file either from a URL or from the local file system
+pub fn load_source(source: &str, http_options: &HttpOptions) -> Result<Vec<u8>> {
+    if utils::is_valid_url(source) {
+        download::fetch_bytes(source, MAX_SOURCE_SIZE, http_options)
+    } else {
+        let path = Path::new(source);
+        fs::read(path).with_context(|| {
+            format!(
+                "Failed to read file: {}",
+                utils::absolute_path_as_string(path)
+            )
+        })
+    }
+}
+
+fn invalid_signature(scheme: SignatureScheme, details: impl std::fmt::Debug) -> anyhow::Error {
+    let signature_err = InvalidSignatureError { scheme };
+    log::error!("{signature_err} - Details: {details:?}");
+    signature_err.into()
+}
+
+// ---------------------------------------------------------------------------
+// OpenPGP
+// ---------------------------------------------------------------------------
+
+/// Verifies an OpenPGP signature of version 4 keys (RSA, ECDSA and EdDSA).
+/// Only the given keys and their signing subkeys are trusted.
+fn verify_openpgp(trusted_keys: &[Vec<u8>], signature: &[u8], data: impl Read) -> Result<String> {
+    let signatures = openpgp::parse_signatures(signature)?;
+
+    let certificates = trusted_keys
+        .iter()
+        .map(|content| openpgp::parse_certificates(content))
+        .collect::<Result<Vec<_>>>()?
+        .concat();
+
+    if certificates.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No supported OpenPGP public key (version 4) found in the given key files"
+        ));
+    }
+
+    // pick the trusted keys, which are named as issuer of a signature
+    let candidates: Vec<_> = signatures
+        .iter()
+        .flat_map(|signature| {
+            certificates.iter().flat_map(move |certificate| {
+                certificate
+                    .keys()
+                    .filter(|key| signature.is_issued_by(key))
+                    .map(move |key| (signature, certificate, key))
+            })
+        })
+        .collect();
+
+    if candidates.is_empty() {
+        return Err(invalid_signature(
+            SignatureScheme::OpenPgp,
+            "the signing key is not trusted",
+        ));
+    }
+
+    // the data is read once and hashed for each candidate
+    let mut hashers: Vec<_> = candidates
+        .iter()
+        .map(|(signature, _, _)| signature.hasher())
+        .collect();
+
+    stream_data(data, |chunk| {
+        hashers.iter_mut().for_each(|hasher| hasher.update(chunk))
+    })?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    let mut errors = vec![];
+    for ((signature, certificate, key), hasher) in candidates.into_iter().zip(hashers) {
+        let result = key
+            .verify(signature, &hasher.finalize(signature))
+            .and_then(|()| certificate.check_validity(key, signature, now));
+        match result {
+            Ok(()) => return Ok(certificate.signer(key)),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+
+    Err(invalid_signature(SignatureScheme::OpenPgp, errors))
+}
+
+// ---------------------------------------------------------------------------
+// minisign
+// ---------------------------------------------------------------------------
+
+fn verify_minisign(
+    trusted_keys: &[Vec<u8>],
+    signature: &[u8],
+    mut data: impl Read,
+) -> Result<String> {
+    let signature_text = String::from_utf8_lossy(signature);
+    let signature = minisign_verify::Signature::decode(&signature_text)
+        .map_err(|err| anyhow::anyhow!("Invalid minisign signature - {err}"))?;
+
+    // the signature line starts with the signature algorithm and the key ID, like a public key
+    let signature_key_id = signature_text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with(MINISIGN_COMMENT_PREFIX))
+        .and_then(minisign_key_id)
+        .ok_or_else(|| anyhow::anyhow!("Invalid minisign signature - Missing key ID"))?;
+
+    let public_keys: Vec<_> = trusted_keys
+        .iter()
+        .flat_map(|content| parse_minisign_keys(&String::from_utf8_lossy(content)))
+        .collect();
+
+    // pick the trusted key with the same key ID as the signature
+    let (key_id, public_key) = public_keys
+        .iter()
+        .find(|(key_id, _)| *key_id == signature_key_id)
+        .ok_or_else(|| {
+            invalid_signature(
+                SignatureScheme::Minisign,
+                format!("no public key with the key ID {signature_key_id}"),
+            )
+        })?;
+
+    match public_key.verify_stream(&signature) {
+        Ok(mut verifier) => {
+            stream_data(data, |chunk| verifier.update(chunk))?;
+            verifier.finalize()
+        }
+        // a legacy signature (minisign -l) covers the data itself instead of its hash
+        Err(minisign_verify::Error::UnsupportedLegacyMode) => {
+            let mut buffer = vec![];
+            data.read_to_end(&mut buffer)?;
+            public_key.verify(&buffer, &signature, true)
+        }
+        Err(err) => Err(err),
+    }
+    .map_err(|err| invalid_signature(SignatureScheme::Minisign, err))?;
+
+    Ok(key_id.to_string())
+}
+
+/// Parses all minisign public keys of a `.pub` file or a list of base64 encoded keys.
+/// Returns the key ID (as shown by `minisign`) together with the key.
+fn parse_minisign_keys(content: &str) -> Vec<(String, minisign_verify::PublicKey)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with(MINISIGN_COMMENT_PREFIX))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let public_key = minisign_verify::PublicKey::from_base64(line).ok()?;
+            Some((minisign_key_id(line)?, public_key))
+        })
+        .collect()
+}
+
+/// The key ID is stored in little endian order behind the two bytes of the signature algorithm
+fn minisign_key_id(public_key_b64: &str) -> Option<String> {
+    let bin = STANDARD.decode(public_key_b64).ok()?;
+    let key_id: [u8; 8] = bin.get(2..10)?.try_into().ok()?;
+    Some(format!("{:016X}", u64::from_le_bytes(key_id)))
+}
+
+// ---------------------------------------------------------------------------
+// SSH
+// ---------------------------------------------------------------------------
+
+fn verify_ssh(
+    trusted_keys: &[Vec<u8>],
+    signature: &[u8],
+    namespace: &str,
+    mut data: impl Read,
+) -> Result<String> {
+    let signature = SshSig::from_pem(signature)
+        .map_err(|err| anyhow::anyhow!("Invalid SSH signature - {err}"))?;
+
+    if signature.namespace() != namespace {
+        return Err(anyhow::anyhow!(
+            "The SSH signature was created for the namespace '{}', expected '{namespace}'",
+            signature.namespace(),
+        ));
+    }
+
+    let public_keys: Vec<_> = trusted_keys
+        .iter()
+        .flat_map(|content| parse_ssh_keys(&String::from_utf8_lossy(content)))
+        .collect();
+
+    let public_key = public_keys
+        .iter()
+        .find(|public_key| public_key.key_data() == signature.public_key())
+        .ok_or_else(|| invalid_signature(SignatureScheme::Ssh, "the signing key is not trusted"))?;
+
+    // the signature covers only the hash of the data, so the data can be streamed
+    let hash = match signature.hash_alg() {
+        HashAlg::Sha256 => stream_digest(sha2::Sha256::new(), &mut data)?,
+        HashAlg::Sha512 => stream_digest(sha2::Sha512::new(), &mut data)?,
+        unsupported => {
+            return Err(anyhow::anyhow!(
+                "Unsupported hash algorithm of the SSH signature: {unsupported}"
+            ));
+        }
+    };
+
+    let signed_data = ssh_signed_data(&signature, &hash);
+
+    public_key
+        .key_data()
+        .verify(&signed_data, signature.signature())
+        .map_err(|err| invalid_signature(SignatureScheme::Ssh, err))?;
+
+    let fingerprint = public_key.fingerprint(HashAlg::Sha256);
+    Ok(if public_key.comment().is_empty() {
+        fingerprint.to_string()
+    } else {
+        format!("{fingerprint} ({})", public_key.comment())
+    })
+}
+
+fn stream_digest<D: Digest>(mut digest: D, data: &mut impl Read) -> Result<Vec<u8>> {
+    stream_data(data, |chunk| digest.update(chunk))?;
+    Ok(digest.finalize().to_vec())
+}
+
+/// Reads the data chunk by chunk and passes each chunk to the consumer
+fn stream_data(mut data: impl Read, mut consume: impl FnMut(&[u8])) -> Result<()> {
+    let mut buf = [0u8; utils::CAPACITY];
+    loop {
+        let n = data.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        consume(&buf[..n]);
+    }
+    Ok(())
+}
+
+/// Builds the blob that is signed by `ssh-keygen -Y sign` (see PROTOCOL.sshsig)
+fn ssh_signed_data(signature: &SshSig, hash: &[u8]) -> Vec<u8> {
+    fn put_string(blob: &mut Vec<u8>, value: &[u8]) {
+        blob.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        blob.extend_from_slice(value);
+    }
+
+    let mut blob = b"SSHSIG".to_vec();
+    put_string(&mut blob, signature.namespace().as_bytes());
+    put_string(&mut blob, signature.reserved());
+    put_string(&mut blob, signature.hash_alg().as_str().as_bytes());
+    put_string(&mut blob, hash);
+    blob
+}
+
+/// Parses public keys in the OpenSSH format. Lines in the format of an `allowed_signers` or
+/// `authorized_keys` file are also accepted, leading principals and options are ignored.
+fn parse_ssh_keys(content: &str) -> Vec<PublicKey> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            (0..tokens.len()).find_map(|idx| PublicKey::from_openssh(&tokens[idx..].join(" ")).ok())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MINISIGN_PUBLIC_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+
+    // prehashed signature of the string 'test'
+    const MINISIGN_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==";
+
+    const MINISIGN_LEGACY_PUBLIC_KEY: &str =
+        "RWQBI0VniavN7wOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+
+    // legacy signature (minisign -l) of the string 'test'
+    const MINISIGN_LEGACY_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RWQBI0VniavN76VXp6pguhWceWrRkO1fvuc88dyHDQ5ampsF9laj0O5bSvJ6m1nBrK8ZEpl5M50JqGgPmEJsV9DTs+MuMKH5/gk=
+trusted comment: timestamp:1792329384\tfile:test
+7pMcXh1n1m9Qycya+kFQzkxmfcEZlw5HUFV9HEzQSMT6muxK1DMUdVmvV5pssSU1Y0mTnS3zdiTcLtMCflQXBA==";
+
+    const SSH_PUBLIC_KEY: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIBENgUR27rt5EjgaCX3ViD64SdkhkFtJcL5GYJuqOPsQ user@example.com";
+
+    // signature of the string 'hello\n' within the namespace 'file'
+    const SSH_SIGNATURE: &str = "-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgEQ2BRHbuu3kSOBoJfdWIPrhJ2S
+GQW0lwvkZgm6o4+xAAAAAEZmlsZQAAAAAAAAAGc2hhNTEyAAAAUwAAAAtzc2gtZWQyNTUx
+OQAAAEAezvaJZWF8dl5Kxr8Y1O4htUcSW9BYTMc7Bn87CS/AEbYA526oADIALk4J2OvgIY
+1XPyfWwsgo/rxjbYse1vAP
+-----END SSH SIGNATURE-----";
+
+    // exported public keys of an RSA key and of an Ed25519 key with a signing subkey
+    const OPENPGP_KEYRING: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mQENBGrU4pIBCACqVxipZJ/6GqeOJw3HcnJ+uWxAaoIfH8ZYTzR6n6bACvha77XM
+RIUGnYG6cFxv4U+MhKpx/Moy1JiYP9ExuHwMf+xUZU2sfCzLGk9aLjt7Dxa4/czN
+ud6OyS3TDqfJ1Ty+q9brLAEwigQNJIEz4NQMeyODENJlJYFERE9/R2KLqgp+EFNa
+okwnooNxXAbDcdFsQeE0lIuxstDRsgjc5Wj3MHS+hd1bak3KZDv6IZZ+v9DtP7c9
+zYrcgPjYiYP40rmX0eoZw4mnJ9opW9e8Z6AbL93oLqoYRdZySgrsNaDaMtVlAlpI
+qKOuItYGlj+kp9opaYfe3QD/hvyouH/xXvprABEBAAG0GlJTQSBUZXN0IDxyc2FA
+ZXhhbXBsZS5jb20+iQFOBBMBCgA4FiEE9zJav1iA+ibpOe2WUw140fKYFEIFAmrU
+4pICGwMFCwkIBwIGFQoJCAsCBBYCAwECHgECF4AACgkQUw140fKYFEJLVQf9HEW1
+sk44J4b+646ipfma4OjpiqVf/V9avpvk3yL+pmyEHGAtCC1IO6eLH3P4Qrm/kmsc
+aSAXAwh1wncYnRKUdpdGscDgMAhkv01+w1CQKh93FF9Z2mgECI/qx4qG6QDC8Ty4
+5P44ebFjA+VKDi0f1xcAyiSjtHnGnWvS35Vf7eNWnYGy7H+epT4RwXmGniss4qun
+m/5/sNm5lsZ5sn3UfaBcCr+0mB3dXjIV0vh4RMa5wU5ITKYrYXpQXGlUgouRFB/5
+yay1OMRhIv/uYhUfnbR83indM/zGgJ4spcAyi6XUNyqfSqoEQDJ3JEP7HK6CzaYl
+IhZOR3+xX6X0nqKfmA==
+=mGVb
+-----END PGP PUBLIC KEY BLOCK-----
+-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatTikhYJKwYBBAHaRw8BAQdA3BN26If1tErYBTdpqjGqA1Oxggh4xYTPILJQ
+z5LJwiu0GEVkIFRlc3QgPGVkQGV4YW1wbGUuY29tPoiQBBMWCAA4FiEEav+wQJEg
+nWTHoisQzVcs7JcUGSEFAmrU4pICGwEFCwkIBwIGFQoJCAsCBBYCAwECHgECF4AA
+CgkQzVcs7JcUGSHJEgD/RPpJTrtlkgQo8CVxdaSLpvHbf9xMfjKY++SwVpe3KgUA
+/2WlYswzjZM5e/2WRA+ao4HwVL/lo0n+GAVLnFZYJAEOuDMEatTikhYJKwYBBAHa
+Rw8BAQdA73rxLeJh5EmHSgoghj2MkZYfQqG3PWZUT1IWJeffvDSI7wQYFggAIBYh
+BGr/sECRIJ1kx6IrEM1XLOyXFBkhBQJq1OKSAhsCAIEJEM1XLOyXFBkhdiAEGRYI
+AB0WIQRTHTr57kVG8Y1VqWosNhxGLI2+tQUCatTikgAKCRAsNhxGLI2+tTxEAQDb
+6/Qem3DTshc6RXIqVHzjVX2D7vjut2GB7e9HvbGgKwEApJb4vQhTsp2X96w2tF4o
+5uV7xwpWCSlc2gwOSIv2owWyowEAwqEFbE2GogiUGHuVX1TKlji43dMCyliyOZOU
+B8TMQfkA/RUVOMi9iFNqyvcJHiVrWgdUSr8Y37C8/2O0SebOwlsF
+=MTUG
+-----END PGP PUBLIC KEY BLOCK-----\n";
+
+    const OPENPGP_ECDSA_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mFIEatTikhMIKoZIzj0DAQcCAwT+xk/nwgioRT/AgYjEe1l6+4TpPruPaLpPXJts
+NB9Og0fQHCUuZF8NH1nf5MU54IwOH/5zlSjM7ahBVrxTdBGftB5FQ0RTQSBUZXN0
+IDxlY2RzYUBleGFtcGxlLmNvbT6IkAQTEwgAOBYhBJ+kbDzGluWsrB6j4qZvaIbh
+A5bXBQJq1OKSAhsDBQsJCAcCBhUKCQgLAgQWAgMBAh4BAheAAAoJEKZvaIbhA5bX
+TBUA/jxCW/ScPEeP7sWftcYBfZIH1oPSHB00epLovo1P4AaBAQCaKh6T91hSggL2
+ZRL7anvzQycEfb+YK3rm6CFlKMsxsg==
+=IltI
+-----END PGP PUBLIC KEY BLOCK-----\n";
+
+    // signatures of the string 'hello\n'
+    const OPENPGP_RSA_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iQFEBAABCgAuFiEE9zJav1iA+ibpOe2WUw140fKYFEIFAmrU4pIQHHJzYUBleGFt
+cGxlLmNvbQAKCRBTDXjR8pgUQou3B/9U2RuumOdyfbSlmE965qMs15/hlUSoy9r8
+qJ8TnDeR452H9BKhx1oDkwTFqNa9fK6tkecGhDnfN/Y2tkBhgBJ/hJs8qB3q4Kvj
+OlwZHOBiTvvDNtAUS852Ah9fFgvIvuxthrxF+zW/5//sLyqGn2n3TLI7N9DVywu7
+fjs4Ourpv0kCufS0uzjyovTDuinySvm0+AlUb9+Vx03SLzqlmFDrPs2tKqIciElm
+TH+yAdrdsWpxF6swzJsezKMLLySKMP84NAWMuKKaBZkRCkDke12vScmI7s4/UqXx
+wFNqUpIwYSQ0MMly2bE89GIg6XDq+4LIgkZ6KdrACw8o4z7+WA6M
+=uu4x
+-----END PGP SIGNATURE-----\n";
+
+    // created by the Ed25519 subkey
+    const OPENPGP_ED25519_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iIUEABYIAC0WIQRTHTr57kVG8Y1VqWosNhxGLI2+tQUCatTikg8cZWRAZXhhbXBs
+ZS5jb20ACgkQLDYcRiyNvrXbSQEA5qN2JYhqAhttn6RAYhxjsNJl4UTGX5tI0Y9/
+5sLBni4A/i1L+dyuOqdwj73K5ZHCdjhudaz/qdMNfM8GRxqzQN4E
+=R0UB
+-----END PGP SIGNATURE-----\n";
+
+    const OPENPGP_ED25519_TEXT_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iIUEARYIAC0WIQRTHTr57kVG8Y1VqWosNhxGLI2+tQUCatTikg8cZWRAZXhhbXBs
+ZS5jb20ACgkQLDYcRiyNvrXB7AEAvl7dXQfVWyJQzbR5rNVgaI1kAmbRxwTHPS7I
+f6N3E9sA/1oZ1mBDbB+AnOttcUgtwFPx3v1Ke9D4jSIgwtUkjRQE
+=ofZw
+-----END PGP SIGNATURE-----\n";
+
+    // binary signature of the ECDSA (P-256) key
+    const OPENPGP_ECDSA_SIGNATURE: &str = "iIgEABMIADAWIQSfpGw8xpblrKweo+Kmb2iG4QOW1wUCatTikhIcZWNkc2FAZXhhbXBsZS5jb20ACgkQpm9ohuEDltcH4wEA1BtBpjHT/9O7B/xEJFgOYvuRdzmj70x0/Nu2LBdI7YUA/R/ppvPZLa2Y3Dv0OtqhSFU+4Lidgl5mesqAh8GASm43";
+
+    #[test]
+    fn test_detect_scheme() {
+        assert_eq!(
+            detect_scheme(b"untrusted comment: signature from minisign secret key\nRWQ..."),
+            Some(SignatureScheme::Minisign)
+        );
+        assert_eq!(
+            detect_scheme(b"-----BEGIN SSH SIGNATURE-----\nU1NIU0lH\n-----END SSH SIGNATURE-----"),
+            Some(SignatureScheme::Ssh)
+        );
+        assert_eq!(
+            detect_scheme(b"\n-----BEGIN PGP SIGNATURE-----\n\niHUEABYKAB0WIQ\n"),
+            Some(SignatureScheme::OpenPgp)
+        );
+        assert_eq!(
+            detect_scheme(&[0x88, 0x75, 0x04, 0x00]),
+            Some(SignatureScheme::OpenPgp)
+        );
+        assert_eq!(detect_scheme(b"Hello World"), None);
+    }
+
+    #[test]
+    fn test_minisign_key_id() {
+        assert_eq!(
+            minisign_key_id(MINISIGN_PUBLIC_KEY),
+            Some("E7620F1842B4E81F".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_minisign_keys() {
+        let content = format!(
+            "untrusted comment: minisign public key E7620F1842B4E81F\n{MINISIGN_PUBLIC_KEY}\n\n# comment\nnot a key\n"
+        );
+        let keys = parse_minisign_keys(&content);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].0, "E7620F1842B4E81F");
+    }
+
+    #[test]
+    fn test_parse_ssh_keys() {
+        let content = format!(
+            "# allowed signers\n{SSH_PUBLIC_KEY}\nuser@example.com namespaces=\"file\" {SSH_PUBLIC_KEY}\ninvalid line\n"
+        );
+        let keys = parse_ssh_keys(&content);
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].comment(), "user@example.com");
+        assert_eq!(keys[0].key_data(), keys[1].key_data());
+    }
+
+    #[test]
+    fn test_verify_minisign() {
+        // the key of the signature is selected by its key ID
+        let trusted_keys = vec![
+            MINISIGN_LEGACY_PUBLIC_KEY.as_bytes().to_vec(),
+            MINISIGN_PUBLIC_KEY.as_bytes().to_vec(),
+        ];
+
+        for (signature, key_id) in [
+            (MINISIGN_SIGNATURE, "E7620F1842B4E81F"),
+            (MINISIGN_LEGACY_SIGNATURE, "EFCDAB8967452301"),
+        ] {
+            assert_eq!(
+                verify_minisign(&trusted_keys, signature.as_bytes(), &b"test"[..]).unwrap(),
+                key_id
+            );
+            let signature_err =
+                verify_minisign(&trusted_keys, signature.as_bytes(), &b"Test"[..]).unwrap_err();
+            assert!(signature_err.is::<InvalidSignatureError>());
+        }
+
+        // the signing key is not trusted
+        let signature_err = verify_minisign(
+            &trusted_keys[..1],
+            MINISIGN_SIGNATURE.as_bytes(),
+            &b"test"[..],
+        )
+        .unwrap_err();
+        assert!(signature_err.is::<InvalidSignatureError>());
+    }
+
+    #[test]
+    fn test_verify_openpgp() {
+        let keyring = vec![OPENPGP_KEYRING.as_bytes().to_vec()];
+        let verify = |trusted_keys: &[Vec<u8>], signature: &[u8], data: &[u8]| {
+            verify_openpgp(trusted_keys, signature, data)
+        };
+
+        assert_eq!(
+            verify(&keyring, OPENPGP_RSA_SIGNATURE.as_bytes(), b"hello\n").unwrap(),
+            "F7325ABF5880FA26E939ED96530D78D1F2981442 (RSA Test <rsa@example.com>)"
+        );
+        // the key of the second armored block of the keyring, signed by its subkey
+        assert_eq!(
+            verify(&keyring, OPENPGP_ED25519_SIGNATURE.as_bytes(), b"hello\n").unwrap(),
+            "531D3AF9EE4546F18D55A96A2C361C462C8DBEB5 (Ed Test <ed@example.com>)"
+        );
+        // text signatures are independent of the line endings
+        for data in [&b"hello\n"[..], b"hello\r\n"] {
+            assert!(verify(&keyring, OPENPGP_ED25519_TEXT_SIGNATURE.as_bytes(), data).is_ok());
+        }
+
+        let ecdsa_key = vec![OPENPGP_ECDSA_KEY.as_bytes().to_vec()];
+        let ecdsa_signature = STANDARD.decode(OPENPGP_ECDSA_SIGNATURE).unwrap();
+        assert_eq!(
+            detect_scheme(&ecdsa_signature),
+            Some(SignatureScheme::OpenPgp)
+        );
+        assert_eq!(
+            verify(&ecdsa_key, &ecdsa_signature, b"hello\n").unwrap(),
+            "9FA46C3CC696E5ACAC1EA3E2A66F6886E10396D7 (ECDSA Test <ecdsa@example.com>)"
+        );
+
+        assert!(verify(&keyring, OPENPGP_RSA_SIGNATURE.as_bytes(), b"hello").is_err());
+        assert!(verify(&keyring, OPENPGP_ED25519_SIGNATURE.as_bytes(), b"hello").is_err());
+        assert!(verify(&ecdsa_key, &ecdsa_signature, b"Hello\n").is_err());
+        // the signing key is not trusted
+        assert!(verify(&ecdsa_key, OPENPGP_RSA_SIGNATURE.as_bytes(), b"hello\n").is_err());
+        assert!(verify(&keyring, &ecdsa_signature, b"hello\n").is_err());
+    }
+
+    #[test]
+    fn test_verify_ssh() {
+        let trusted_keys = vec![SSH_PUBLIC_KEY.as_bytes().to_vec()];
+        let signature = SSH_SIGNATURE.as_bytes();
+
+        let signer = verify_ssh(&trusted_keys, signature, "file", &b"hello\n"[..]).unwrap();
+        assert!(signer.starts_with("SHA256:"));
+        assert!(signer.ends_with("(user@example.com)"));
+
+        assert!(verify_ssh(&trusted_keys, signature, "file", &b"hello"[..]).is_err());
+        assert!(verify_ssh(&trusted_keys, signature, "git", &b"hello\n"[..]).is_err());
+        assert!(verify_ssh(&[], signature, "file", &b"hello\n"[..]).is_err());
+    }
+}
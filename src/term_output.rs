@@ -1,27 +1,156 @@
 use anyhow::Result;
-use std::io::Write;
+use clap::ValueEnum;
+use indicatif::ProgressBar;
+use std::{
+    ffi::OsString,
+    io::{IsTerminal, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
-use crate::{command_handling::CommandResult, utils};
+use crate::{
+    batch::BatchEntryResult,
+    command_handling::{CommandResult, ResultDetails},
+    config::{self, Setting},
+    http_client,
+    integrity::IntegrityCheck,
+    metalink::PieceVerification,
+    output_template::OutputTemplate,
+    signature::SignatureVerification,
+    utils,
+};
+
+// Max. number of corrupted pieces that are listed
+const MAX_LISTED_PIECES: usize = 10;
+
+// Interval of the plain progress lines, which are printed instead of a progress bar
+const PLAIN_PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
 
 pub const BOUNCING_BAR: [&str; 16] = [
     "[    ]", "[=   ]", "[==  ]", "[=== ]", "[====]", "[ ===]", "[  ==]", "[   =]", "[    ]",
     "[   =]", "[  ==]", "[ ===]", "[====]", "[=== ]", "[==  ]", "[=   ]",
 ];
 
-pub fn get_stdout(no_color: bool) -> StandardStream {
-    if no_color {
-        StandardStream::stdout(ColorChoice::Never)
-    } else {
-        StandardStream::stdout(ColorChoice::Always)
+/// When colored output is used
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// colors are used if the output is a terminal, respects NO_COLOR and CLICOLOR_FORCE
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Amount of information about the command result
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutputLevel {
+    /// only the calculated hash, nothing if a hash is compared
+    Quiet,
+    #[default]
+    Normal,
+    /// additional details, e.g. the file size, the elapsed time and the response headers
+    Verbose,
+}
+
+pub fn get_stdout(color: ColorMode) -> StandardStream {
+    StandardStream::stdout(color_choice(
+        color,
+        std::io::stdout().is_terminal(),
+        |name: &str| std::env::var_os(name),
+    ))
+}
+
+/// Standard error stream, used for all output if the standard output is occupied by the downloaded data
+pub fn get_stderr(color: ColorMode) -> StandardStream {
+    StandardStream::stderr(color_choice(
+        color,
+        std::io::stderr().is_terminal(),
+        |name: &str| std::env::var_os(name),
+    ))
+}
+
+/// In auto mode colors are disabled by the environment variable `NO_COLOR` (see <https://no-color.org>)
+/// or if the output is not a terminal, e.g. a CI log. `CLICOLOR_FORCE` forces colors in any case.
+/// The value of an environment variable is returned by `env_var`.
+fn color_choice(
+    color: ColorMode,
+    is_terminal: bool,
+    env_var: impl Fn(&str) -> Option<OsString>,
+) -> ColorChoice {
+    let env_is_set = |name| env_var(name).is_some_and(|value| !value.is_empty());
+    let color_forced = env_var("CLICOLOR_FORCE").is_some_and(|value| value != "0");
+
+    match color {
+        ColorMode::Always => ColorChoice::Always,
+        ColorMode::Never => ColorChoice::Never,
+        ColorMode::Auto if color_forced => ColorChoice::Always,
+        ColorMode::Auto if env_is_set("NO_COLOR") || !is_terminal => ColorChoice::Never,
+        ColorMode::Auto => ColorChoice::Always,
     }
 }
 
-fn write_input_source(mut stdout: &mut StandardStream, cmd_result: &CommandResult) -> Result<()> {
-    let source = match &cmd_result.file_location {
-        Some(file_location) => utils::absolute_path_as_string(file_location),
-        None => match &cmd_result.buffer {
-            Some(buffer) => format!("Buffer of size {} byte(s)", buffer.len()),
+/// Prints the progress as plain text line every few seconds, if the progress bar is not drawn
+/// (e.g. in a CI log). The lines are printed until the progress bar is finished or dropped.
+pub fn print_plain_progress(progress_bar: &ProgressBar, label: &str) {
+    // progress bars are only drawn, if the standard error is a terminal
+    if std::io::stderr().is_terminal() {
+        return;
+    }
+
+    let progress_bar = progress_bar.downgrade();
+    let label = label.to_string();
+
+    let spawn_result = std::thread::Builder::new()
+        .name("Progress-Report-Thread".to_string())
+        .spawn(move || {
+            let mut last_report = Instant::now();
+
+            loop {
+                std::thread::sleep(Duration::from_millis(250));
+
+                let Some(progress_bar) = progress_bar.upgrade() else {
+                    break;
+                };
+                if progress_bar.is_finished() {
+                    break;
+                }
+
+                if last_report.elapsed() >= PLAIN_PROGRESS_INTERVAL {
+                    last_report = Instant::now();
+                    eprintln!(
+                        "{}",
+                        plain_progress_line(&label, progress_bar.position(), progress_bar.length())
+                    );
+                }
+            }
+        });
+
+    if let Err(spawn_err) = spawn_result {
+        log::debug!("Failed to start the progress report - Details: {spawn_err:?}");
+    }
+}
+
+fn plain_progress_line(label: &str, position: u64, length: Option<u64>) -> String {
+    let processed = utils::convert_bytes_to_human_readable(position as usize);
+
+    match length.filter(|length| *length > 0) {
+        Some(length) => format!(
+            "{label}: {}% ({processed} of {})",
+            position * 100 / length,
+            utils::convert_bytes_to_human_readable(length as usize)
+        ),
+        None => format!("{label}: {processed}"),
+    }
+}
+
+fn write_input_source(mut stdout: &mut impl WriteColor, cmd_result: &CommandResult) -> Result<()> {
+    let source = match (&cmd_result.file_location, &cmd_result.buffer) {
+        (Some(file_location), _) => utils::absolute_path_as_string(file_location),
+        (None, Some(buffer)) => format!("Buffer of size {} byte(s)", buffer.len()),
+        // downloaded data that was not saved
+        (None, None) => match &cmd_result.download_source {
+            Some(download_source) => format!("{download_source} [not saved]"),
             None => "Buffer of unknown size".to_string(),
         },
     };
@@ -30,10 +159,71 @@ fn write_input_source(mut stdout: &mut StandardStream, cmd_result: &CommandResul
     write!(&mut stdout, "\nInput source   : ")?;
     stdout.reset()?;
     writeln!(&mut stdout, "{}", source)?;
+
+    if let (Some(download_source), Some(_)) =
+        (&cmd_result.download_source, &cmd_result.file_location)
+    {
+        writeln!(&mut stdout, "Download source: {download_source}")?;
+    }
+
+    if let Some((first_url, redirects)) = cmd_result.redirect_chain.split_first() {
+        writeln!(&mut stdout, "Redirect chain : {first_url}")?;
+        for url in redirects {
+            writeln!(&mut stdout, "                 -> {url}")?;
+        }
+    }
+
+    if cmd_result.from_cache {
+        writeln!(
+            &mut stdout,
+            "Cached         : not modified since the last download, the local file was verified again"
+        )?;
+    }
     Ok(())
 }
 
-fn write_calculated_hash(stdout: &mut StandardStream, hash: &str) -> Result<()> {
+fn write_details(stdout: &mut impl WriteColor, details: &ResultDetails) -> Result<()> {
+    if let Some(size) = details.size {
+        writeln!(
+            stdout,
+            "File size      : {} ({size} bytes)",
+            utils::convert_bytes_to_human_readable(size as usize)
+        )?;
+    }
+
+    if let Some(walked_files) = details.walked_files {
+        writeln!(stdout, "Files walked   : {walked_files}")?;
+    }
+
+    if let Some(elapsed) = details.elapsed {
+        writeln!(stdout, "Elapsed time   : {:.3}s", elapsed.as_secs_f64())?;
+
+        if let Some(size) = details.size
+            && !elapsed.is_zero()
+        {
+            let bytes_per_sec = size as f64 / elapsed.as_secs_f64();
+            writeln!(
+                stdout,
+                "Throughput     : {}/s",
+                utils::convert_bytes_to_human_readable(bytes_per_sec as usize)
+            )?;
+        }
+    }
+
+    if let Some(final_url) = &details.final_url {
+        writeln!(stdout, "Final URL      : {final_url}")?;
+    }
+
+    if let Some(((first_name, first_value), headers)) = details.response_headers.split_first() {
+        writeln!(stdout, "Response header: {first_name}: {first_value}")?;
+        for (name, value) in headers {
+            writeln!(stdout, "                 {name}: {value}")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_calculated_hash(stdout: &mut impl WriteColor, hash: &str) -> Result<()> {
     let calculated_hash_sum = format!("Calculated hash: {hash}");
 
     log::info!("{calculated_hash_sum}");
@@ -43,7 +233,7 @@ fn write_calculated_hash(stdout: &mut StandardStream, hash: &str) -> Result<()>
 
 /// Prints the given hash and highlights all differing bytes compared to the calculated hash.
 pub fn write_given_hash(
-    mut stdout: &mut StandardStream,
+    mut stdout: &mut impl WriteColor,
     given_hash: &str,
     calculated_hash: &str,
 ) -> Result<()> {
@@ -83,7 +273,7 @@ pub fn write_given_hash(
     Ok(())
 }
 
-fn write_match_status(stdout: &mut StandardStream, is_equal: bool) -> Result<()> {
+fn write_match_status(stdout: &mut impl WriteColor, is_equal: bool) -> Result<()> {
     let (msg, color) = if is_equal {
         ("Hash sums match", Color::Green)
     } else {
@@ -97,7 +287,93 @@ fn write_match_status(stdout: &mut StandardStream, is_equal: bool) -> Result<()>
     Ok(())
 }
 
-fn write_algorithm(stdout: &mut StandardStream, text: &str, algorithm: &str) -> Result<()> {
+fn write_signature_verification(
+    stdout: &mut impl WriteColor,
+    verification: &SignatureVerification,
+) -> Result<()> {
+    stdout.set_color(ColorSpec::new().set_fg(Some(Color::Green)).set_bold(true))?;
+    write!(stdout, "Signature      : ")?;
+    reset_color(stdout)?;
+    writeln!(
+        stdout,
+        "Valid {} signature for {}",
+        verification.scheme, verification.signed_object
+    )?;
+    writeln!(stdout, "Signer key     : {}", verification.signer_key_id)?;
+    Ok(())
+}
+
+fn write_piece_verification(
+    stdout: &mut impl WriteColor,
+    verification: &PieceVerification,
+) -> Result<()> {
+    let color = if verification.is_valid() {
+        Color::Green
+    } else {
+        Color::Red
+    };
+
+    stdout.set_color(ColorSpec::new().set_fg(Some(color)).set_bold(true))?;
+    write!(stdout, "Piece hashes   : ")?;
+    reset_color(stdout)?;
+
+    if verification.is_valid() {
+        writeln!(
+            stdout,
+            "All {} piece(s) match - {}",
+            verification.total, verification.algorithm
+        )?;
+    } else {
+        if !verification.corrupted.is_empty() {
+            let mut corrupted = verification
+                .corrupted
+                .iter()
+                .take(MAX_LISTED_PIECES)
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            if verification.corrupted.len() > MAX_LISTED_PIECES {
+                corrupted.push_str(", ...");
+            }
+
+            writeln!(
+                stdout,
+                "{} of {} piece(s) DO NOT match - {} - Corrupted pieces: {corrupted}",
+                verification.corrupted.len(),
+                verification.total,
+                verification.algorithm
+            )?;
+        }
+
+        if verification.surplus_bytes > 0 {
+            if !verification.corrupted.is_empty() {
+                write!(stdout, "                 ")?;
+            }
+            writeln!(
+                stdout,
+                "The file is {} byte(s) longer than the {} piece(s) - {}",
+                verification.surplus_bytes, verification.total, verification.algorithm
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_integrity_check(stdout: &mut impl WriteColor, check: &IntegrityCheck) -> Result<()> {
+    let (msg, color) = if check.is_valid {
+        ("Match", Color::Green)
+    } else {
+        ("DOES NOT match", Color::Red)
+    };
+
+    stdout.set_color(ColorSpec::new().set_fg(Some(color)).set_bold(true))?;
+    write!(stdout, "Server digest  : ")?;
+    reset_color(stdout)?;
+    writeln!(stdout, "{msg} - {} ({})", check.header, check.algorithm)?;
+    Ok(())
+}
+
+fn write_algorithm(stdout: &mut impl WriteColor, text: &str, algorithm: &str) -> Result<()> {
     write!(stdout, "{text}")?;
     stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
     writeln!(stdout, "{}", algorithm)?;
@@ -106,19 +382,45 @@ fn write_algorithm(stdout: &mut StandardStream, text: &str, algorithm: &str) ->
     Ok(())
 }
 
-pub fn reset_color(stdout: &mut StandardStream) -> Result<()> {
+pub fn reset_color(stdout: &mut impl WriteColor) -> Result<()> {
     stdout.reset()?;
     stdout.set_color(&ColorSpec::new())?;
     Ok(())
 }
 
-/// Printing the command result
-pub fn print_result(cmd_result: &CommandResult, no_color: bool) -> Result<()> {
-    let mut output_stream = get_stdout(no_color);
+/// Printing the command result into the given output stream
+pub fn print_result(
+    cmd_result: &CommandResult,
+    output_level: OutputLevel,
+    mut output_stream: impl WriteColor,
+) -> Result<()> {
+    if output_level == OutputLevel::Quiet {
+        // the exit status tells whether the given hash matches
+        if cmd_result.hash_compare_result.is_none() {
+            writeln!(output_stream, "{}", cmd_result.calculated_hash_sum)?;
+        }
+        return Ok(());
+    }
 
     write_input_source(&mut output_stream, cmd_result)?;
     write_calculated_hash(&mut output_stream, &cmd_result.calculated_hash_sum)?;
 
+    if output_level == OutputLevel::Verbose {
+        write_details(&mut output_stream, &cmd_result.details)?;
+    }
+
+    if let Some(verification) = &cmd_result.signature_verification {
+        write_signature_verification(&mut output_stream, verification)?;
+    }
+
+    if let Some(verification) = &cmd_result.piece_verification {
+        write_piece_verification(&mut output_stream, verification)?;
+    }
+
+    for check in &cmd_result.integrity_checks {
+        write_integrity_check(&mut output_stream, check)?;
+    }
+
     if let Some(hash_to_compare) = &cmd_result.hash_compare_result {
         write_given_hash(
             &mut output_stream,
@@ -142,3 +444,330 @@ pub fn print_result(cmd_result: &CommandResult, no_color: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// Printing the command result with the given template instead of the default layout
+pub fn print_formatted(
+    cmd_result: &CommandResult,
+    template: &OutputTemplate,
+    mut output_stream: StandardStream,
+) -> Result<()> {
+    let output = template.render(cmd_result);
+
+    log::info!("Formatted result: {output}");
+    writeln!(output_stream, "{output}")?;
+    Ok(())
+}
+
+/// Printing the location of the config file and the effective settings of all commands
+pub fn print_settings(
+    config_file: Option<&Path>,
+    settings: &[Setting],
+    mut output_stream: StandardStream,
+) -> Result<()> {
+    output_stream.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
+    write!(output_stream, "\nConfig file    : ")?;
+    reset_color(&mut output_stream)?;
+    match config_file {
+        Some(config_file) => writeln!(
+            output_stream,
+            "{}",
+            utils::absolute_path_as_string(config_file)
+        )?,
+        None => writeln!(
+            output_stream,
+            "none - searched in: {}",
+            config::default_locations()
+                .iter()
+                .map(|path| utils::absolute_path_as_string(path))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?,
+    }
+
+    // the width of the name column depends on the longest name
+    let name_width = settings
+        .iter()
+        .map(|setting| setting.name.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut scope = "";
+    for setting in settings {
+        if setting.scope != scope {
+            scope = &setting.scope;
+            output_stream.set_color(ColorSpec::new().set_bold(true))?;
+            writeln!(output_stream, "\n[{scope}]")?;
+            reset_color(&mut output_stream)?;
+        }
+
+        writeln!(
+            output_stream,
+            "{:<name_width$} = {} ({})",
+            setting.name, setting.value, setting.source
+        )?;
+    }
+
+    writeln!(output_stream)?;
+    Ok(())
+}
+
+/// Printing the summary of a batch download as table
+pub fn print_batch_summary(results: &[BatchEntryResult], color: ColorMode) -> Result<()> {
+    let mut stdout = get_stdout(color);
+
+    let failed = results.iter().filter(|result| result.is_failure()).count();
+
+    stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
+    write!(stdout, "\nBatch summary  : ")?;
+    reset_color(&mut stdout)?;
+    writeln!(
+        stdout,
+        "{} download(s), {} succeeded, {} failed\n",
+        results.len(),
+        results.len() - failed,
+        failed
+    )?;
+
+    // the width of the hash column depends on the longest hash
+    let hash_width = results
+        .iter()
+        .filter_map(|entry_result| entry_result.result.as_ref().ok())
+        .map(|cmd_result| cmd_result.calculated_hash_sum.len())
+        .max()
+        .unwrap_or_default()
+        .max("HASH".len());
+
+    writeln!(
+        stdout,
+        "{:<8} {:<10} {:<10} {:<hash_width$} FILE",
+        "STATUS", "HASH CHECK", "ALGORITHM", "HASH"
+    )?;
+
+    for entry_result in results {
+        let (status, color) = if entry_result.is_failure() {
+            ("FAILED", Color::Red)
+        } else {
+            ("OK", Color::Green)
+        };
+
+        stdout.set_color(ColorSpec::new().set_fg(Some(color)).set_bold(true))?;
+        write!(stdout, "{status:<8} ")?;
+        reset_color(&mut stdout)?;
+
+        match &entry_result.result {
+            Ok(cmd_result) => {
+                let hash_check = match &cmd_result.hash_compare_result {
+                    Some(compare_result) if compare_result.is_equal => "match",
+                    Some(_) => "MISMATCH",
+                    None => "-",
+                };
+                let file = cmd_result
+                    .file_location
+                    .as_deref()
+                    .map(utils::absolute_path_as_string)
+                    .unwrap_or_default();
+
+                writeln!(
+                    stdout,
+                    "{:<10} {:<10} {:<hash_width$} {}",
+                    hash_check,
+                    cmd_result.used_algorithm.to_string(),
+                    cmd_result.calculated_hash_sum,
+                    file
+                )?;
+            }
+            Err(_) => {
+                writeln!(
+                    stdout,
+                    "{:<10} {:<10} {:<hash_width$} {}",
+                    "-",
+                    "-",
+                    "-",
+                    http_client::redact_url(&entry_result.url)
+                )?;
+            }
+        }
+    }
+
+    let errors: Vec<_> = results
+        .iter()
+        .filter_map(|entry_result| {
+            entry_result
+                .result
+                .as_ref()
+                .err()
+                .map(|err| (&entry_result.url, err))
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
+        writeln!(stdout, "\nErrors:")?;
+        reset_color(&mut stdout)?;
+        for (url, err) in errors {
+            writeln!(stdout, "- {}\n  {err}", http_client::redact_url(url))?;
+        }
+    }
+
+    writeln!(stdout)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{command_handling::HashCompareResult, hasher::Algorithm};
+    use std::time::Duration;
+
+    const HASH: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn render_result(
+        hash_compare_result: Option<HashCompareResult>,
+        output_level: OutputLevel,
+    ) -> String {
+        let cmd_result = CommandResult {
+            file_location: None,
+            buffer: Some("hello".to_string()),
+            used_algorithm: Algorithm::SHA2_256,
+            calculated_hash_sum: HASH.to_string(),
+            hash_compare_result,
+            signature_verification: None,
+            download_source: None,
+            piece_verification: None,
+            integrity_checks: vec![],
+            redirect_chain: vec![],
+            from_cache: false,
+            details: ResultDetails {
+                size: Some(2048),
+                elapsed: Some(Duration::from_secs(2)),
+                final_url: Some("https://example.com/file.zip".to_string()),
+                response_headers: vec![
+                    ("content-type".to_string(), "application/zip".to_string()),
+                    ("etag".to_string(), "\"abc\"".to_string()),
+                ],
+                walked_files: None,
+            },
+        };
+
+        let mut buffer = termcolor::Buffer::no_color();
+        print_result(&cmd_result, output_level, &mut buffer).unwrap();
+        String::from_utf8(buffer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_print_result_output_levels() {
+        let mismatch = || {
+            Some(HashCompareResult {
+                is_equal: false,
+                given_hash: HASH.replace('2', "3"),
+            })
+        };
+
+        // quiet: only the hash, nothing if a hash was compared
+        assert_eq!(render_result(None, OutputLevel::Quiet), format!("{HASH}\n"));
+        assert_eq!(render_result(mismatch(), OutputLevel::Quiet), "");
+
+        let normal = render_result(mismatch(), OutputLevel::Normal);
+        assert!(normal.contains(&format!("Calculated hash: {HASH}")));
+        assert!(normal.contains("Hash sums DO NOT match"));
+        assert!(!normal.contains("File size"));
+
+        // verbose: the details follow the calculated hash
+        let verbose = render_result(None, OutputLevel::Verbose);
+        let details = format!(
+            "Calculated hash: {HASH}\n\
+             File size      : {} (2048 bytes)\n\
+             Elapsed time   : 2.000s\n\
+             Throughput     : {}/s\n\
+             Final URL      : https://example.com/file.zip\n\
+             Response header: content-type: application/zip\n                 \
+             etag: \"abc\"\n",
+            utils::convert_bytes_to_human_readable(2048),
+            utils::convert_bytes_to_human_readable(1024)
+        );
+        assert!(verbose.contains(&details), "{verbose}");
+        assert!(verbose.contains("Used algorithm: SHA2-256"));
+    }
+
+    #[test]
+    fn test_plain_progress_line() {
+        assert_eq!(
+            plain_progress_line("Download file.zip", 512, Some(2048)),
+            format!(
+                "Download file.zip: 25% ({} of {})",
+                utils::convert_bytes_to_human_readable(512),
+                utils::convert_bytes_to_human_readable(2048)
+            )
+        );
+        assert_eq!(
+            plain_progress_line("Hash calculation", 512, None),
+            format!(
+                "Hash calculation: {}",
+                utils::convert_bytes_to_human_readable(512)
+            )
+        );
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(env_name, _)| *env_name == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+
+        assert_eq!(
+            color_choice(ColorMode::Never, true, env(&[])),
+            ColorChoice::Never
+        );
+        assert_eq!(
+            color_choice(ColorMode::Always, false, env(&[])),
+            ColorChoice::Always
+        );
+
+        assert_eq!(
+            color_choice(ColorMode::Auto, true, env(&[])),
+            ColorChoice::Always
+        );
+        assert_eq!(
+            color_choice(ColorMode::Auto, false, env(&[])),
+            ColorChoice::Never
+        );
+
+        let no_color = env(&[("NO_COLOR", "1")]);
+        assert_eq!(
+            color_choice(ColorMode::Auto, true, no_color),
+            ColorChoice::Never
+        );
+        assert_eq!(
+            color_choice(ColorMode::Always, true, no_color),
+            ColorChoice::Always
+        );
+
+        // an empty NO_COLOR is ignored
+        let empty = env(&[("NO_COLOR", "")]);
+        assert_eq!(
+            color_choice(ColorMode::Auto, true, empty),
+            ColorChoice::Always
+        );
+
+        // CLICOLOR_FORCE takes precedence over NO_COLOR and a missing terminal
+        let forced = env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]);
+        assert_eq!(
+            color_choice(ColorMode::Auto, false, forced),
+            ColorChoice::Always
+        );
+        assert_eq!(
+            color_choice(ColorMode::Never, true, forced),
+            ColorChoice::Never
+        );
+
+        let not_forced = env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "0")]);
+        assert_eq!(
+            color_choice(ColorMode::Auto, true, not_forced),
+            ColorChoice::Never
+        );
+    }
+}
@@ -1,13 +1,14 @@
 use anyhow::Result;
 use chrono::Local;
 use path_absolutize::Absolutize;
-use regex::Regex;
 use std::path::Path;
 use url::Url;
 
 use crate::{
     app,
     command_handling::CommandResult,
+    content_disposition::ContentDisposition,
+    filename_handling,
     os_specifics::{self, OS},
     utils,
 };
@@ -41,11 +42,14 @@ pub fn save_hash_sum(cmd_result: &CommandResult, save: bool) -> Result<()> {
                 cmd_result.calculated_hash_sum,
                 utils::absolute_path_as_string(file_path)
             )
+        } else if let Some(buffer) = &cmd_result.buffer {
+            format!("{}\t{}", cmd_result.calculated_hash_sum, buffer)
         } else {
+            // downloaded data that was not saved
             format!(
                 "{}\t{}",
                 cmd_result.calculated_hash_sum,
-                cmd_result.buffer.as_deref().unwrap_or_default()
+                cmd_result.download_source.as_deref().unwrap_or_default()
             )
         };
 
@@ -56,6 +60,37 @@ pub fn save_hash_sum(cmd_result: &CommandResult, save: bool) -> Result<()> {
     Ok(())
 }
 
+/// Saves the calculated hash sums of several files in one file, e.g. after a batch download.
+/// Each line is prefixed by the used algorithm, so that it can be passed as hash again.
+/// Filename Format: hash-sums-<Timestamp>.txt
+pub fn save_hash_sums(cmd_results: &[&CommandResult], save: bool) -> Result<()> {
+    if save && !cmd_results.is_empty() {
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S_%3f");
+        let file_name = format!("hash-sums-{}.txt", timestamp);
+
+        let content = cmd_results
+            .iter()
+            .map(|cmd_result| {
+                format!(
+                    "{}:{}\t{}",
+                    cmd_result.used_algorithm.to_string().to_lowercase(),
+                    cmd_result.calculated_hash_sum,
+                    cmd_result
+                        .file_location
+                        .as_deref()
+                        .map(utils::absolute_path_as_string)
+                        .unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        std::fs::write(app::data_dir().join(file_name), content)?;
+    }
+
+    Ok(())
+}
+
 /// Gives you the correct time unit dependent on the remaining seconds.
 /// Example:
 ///
@@ -111,6 +146,35 @@ pub fn is_valid_url(url: &str) -> bool {
     }
 }
 
+/// Checks if the given URL is a valid source of the download command.
+/// Besides HTTP(S) URLs, `file:` URLs of a local file and `data:` URLs are supported.
+pub fn is_valid_download_url(url: &str) -> bool {
+    is_valid_url(url)
+        || match Url::parse(url) {
+            Ok(parsed_url) => match parsed_url.scheme() {
+                "file" => parsed_url.to_file_path().is_ok(),
+                "data" => url.contains(','),
+                _ => false,
+            },
+            Err(_) => false,
+        }
+}
+
+/// Returns true, if the URL refers to a local file (`file:`) or contains the data itself (`data:`)
+pub fn is_local_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "file" | "data"))
+}
+
+/// Converts the path of an existing file into a `file:` URL
+pub fn file_url(path: &Path) -> Option<String> {
+    if !path.is_file() {
+        return None;
+    }
+
+    let path = path.absolutize().ok()?;
+    Url::from_file_path(path).ok().map(|url| url.to_string())
+}
+
 /// Extracts the file name from the provided URL.
 ///
 /// This function parses the given URL using the `url` crate, extracting the last segment
@@ -157,40 +221,19 @@ pub fn extract_file_name_from_url(url: &str) -> Option<String> {
 
 /// Try to extract the filename from the server response
 pub fn extract_file_name(url: &str, content_disposition: &str, os_type: &OS) -> Option<String> {
-    // Attempt to extract the filename from Content-Disposition or fallback to the URL path
-    let filename = extract_filename_from_content_disposition(content_disposition)
-        .or_else(|| extract_file_name_from_url(url));
-
-    // If a filename is found, process it
-    filename
-        .map(|f| decode_percent_encoded_to_utf_8(&f))
-        .map(|f| replace_invalid_chars_with_underscore(&f, os_type))
+    // the name of the header is already decoded, only the URL path is percent-encoded
+    extract_filename_from_content_disposition(content_disposition)
+        .and_then(|f| filename_handling::sanitize_filename(os_type, &f))
+        .or_else(|| {
+            extract_file_name_from_url(url)
+                .map(|f| decode_percent_encoded_to_utf_8(&f))
+                .and_then(|f| filename_handling::sanitize_filename(os_type, &f))
+        })
 }
 
-/// Function to extract filename from Content-Disposition header
+/// Extracts the file name from a Content-Disposition header, see [`ContentDisposition::file_name`]
 pub fn extract_filename_from_content_disposition(header_value: &str) -> Option<String> {
-    if !header_value.to_lowercase().starts_with("attachment;") || header_value.trim().is_empty() {
-        return None;
-    }
-
-    let filename_prefixes = ["filename*=", "filename="];
-    let utf8_regex = Regex::new(r"(?i)utf-8").unwrap(); // Case-insensitive regex for "utf-8"
-
-    for part in header_value.split(';').map(str::trim) {
-        for prefix in &filename_prefixes {
-            if let Some(filename) = part.strip_prefix(prefix) {
-                let filename = utf8_regex
-                    .replace_all(filename, "")
-                    .trim_matches(|c| matches!(c, ' ' | '\t' | '\n' | '\r' | '"' | '\''))
-                    .to_string();
-                if !filename.is_empty() {
-                    return Some(filename);
-                }
-            }
-        }
-    }
-
-    None
+    ContentDisposition::parse(header_value).file_name()
 }
 
 /// Decodes a percent-encoded UTF-8 string.
@@ -289,11 +332,37 @@ pub fn convert_bytes_to_human_readable(bytes: usize) -> String {
     }
 }
 
+/// Parses a size in bytes with an optional binary unit suffix (e.g. `500K`, `2M`, `1G`)
+pub fn parse_byte_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, factor) = match size.char_indices().last()? {
+        (idx, 'k' | 'K') => (&size[..idx], KIB),
+        (idx, 'm' | 'M') => (&size[..idx], MIB),
+        (idx, 'g' | 'G') => (&size[..idx], GIB),
+        _ => (size, 1.0),
+    };
+
+    let bytes = number.trim().parse::<f64>().ok()? * factor;
+
+    (bytes.is_finite() && bytes >= 1.0).then_some(bytes as u64)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use os_specifics::OS;
 
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("512"), Some(512));
+        assert_eq!(parse_byte_size("500K"), Some(512_000));
+        assert_eq!(parse_byte_size("1.5m"), Some(1_572_864));
+        assert_eq!(parse_byte_size("1G"), Some(1_073_741_824));
+        assert_eq!(parse_byte_size("0"), None);
+        assert_eq!(parse_byte_size("-1K"), None);
+        assert_eq!(parse_byte_size("fast"), None);
+    }
+
     #[test]
     fn test_valid_url_1() {
         let test_url = "http://example.com/files/document.pdf";
@@ -322,6 +391,16 @@ mod test {
         assert!(!is_valid_url(test_url));
     }
 
+    #[test]
+    fn test_valid_download_url() {
+        assert!(is_valid_download_url("https://example.com/file.zip"));
+        assert!(is_valid_download_url("file:///tmp/foo"));
+        assert!(is_valid_download_url("data:text/plain;base64,SGVsbG8="));
+        assert!(!is_valid_download_url("file://remote-host/tmp/foo"));
+        assert!(!is_valid_download_url("ftp://example.com/file.zip"));
+        assert!(!is_valid_download_url("data:no-comma"));
+    }
+
     #[test]
     fn test_invalid_url_3() {
         let test_url = "www.example.com";
@@ -444,10 +523,10 @@ mod test {
     }
 
     #[test]
-    fn test_invalid_header() {
+    fn test_inline_header() {
         assert_eq!(
             extract_filename_from_content_disposition("inline; filename=\"example.txt\""),
-            None
+            Some("example.txt".to_string())
         );
     }
 